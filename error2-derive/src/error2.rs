@@ -5,23 +5,80 @@ use proc_macro2::TokenStream;
 use quote::{ToTokens, format_ident, quote};
 use syn::{
     Data, DataEnum, DataStruct, DataUnion, DeriveInput, Field, Fields, FieldsNamed, FieldsUnnamed,
-    Generics, Ident, Path, Token, Type, Variant, Visibility, parse_quote, punctuated::Punctuated,
+    GenericArgument, Generics, Ident, LitInt, LitStr, Path, PathArguments, Token, Type, TypePath,
+    Variant, Visibility, parse_quote, punctuated::Punctuated, spanned::Spanned,
 };
 
 use crate::{
     generics::{InferredBounds, ParamsInScope},
     messages::{
-        AT_LEAST_ONE_FIELD, AT_LEAST_ONE_VARIANT, DISPLAY_TOKENS_NOT_ON_ENUM,
-        MISSING_DISPLAY_ON_VARIANT, SUPPORTED_TYPES, incorrect_def,
+        AT_LEAST_ONE_FIELD, AT_LEAST_ONE_VARIANT, DISPLAY_TOKENS_NOT_ON_ENUM, MAX_SIZE_ON_GENERIC,
+        MISSING_DISPLAY_ON_VARIANT, MULTIPLE_SOURCES_FIELDS, REDACT_ON_SPECIAL_FIELD,
+        SOURCE_AND_SOURCES, SOURCES_MUST_BE_VEC, SOURCES_WITHOUT_BACKTRACE, SUPPORTED_TYPES,
+        incorrect_def,
+    },
+    parser::{parse_field_attr, parse_type_attr, parse_variant_attr},
+    serialize::{generate_deserialize, generate_serialize},
+    types::{
+        ContextKind, ErrorKind, FieldAttr, MyVariant, SerdeShape, Trait, TypeAttr, TypeDisplayAttr,
+        VariantAttr,
     },
-    parser::{parse_type_attr, parse_variant_attr},
-    types::{ContextKind, ErrorKind, MyVariant, Trait, TypeAttr, TypeDisplayAttr, VariantAttr},
 };
 
 fn crate_path() -> TokenStream {
     quote! { ::error2 }
 }
 
+fn vec_element_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(TypePath { qself: None, path }) = ty else {
+        return None;
+    };
+
+    let segment = path.segments.last()?;
+
+    if segment.ident != "Vec" {
+        return None;
+    }
+
+    let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+
+    match arguments.args.first() {
+        Some(GenericArgument::Type(ty)) if arguments.args.len() == 1 => Some(ty),
+        _ => None,
+    }
+}
+
+fn generate_size_assertion(
+    type_ident: &Ident,
+    generics: &Generics,
+    max_size: Option<LitInt>,
+) -> syn::Result<TokenStream> {
+    let Some(max_size) = max_size else {
+        return Ok(quote! {});
+    };
+
+    if !generics.params.is_empty() {
+        return Err(syn::Error::new(max_size.span(), MAX_SIZE_ON_GENERIC));
+    }
+
+    let message = LitStr::new(
+        &format!(
+            "size of `{}` exceeds `max_size` of {} bytes",
+            type_ident, max_size
+        ),
+        max_size.span(),
+    );
+
+    Ok(quote! {
+        const _: () = ::core::assert!(
+            ::core::mem::size_of::<#type_ident>() <= #max_size,
+            #message
+        );
+    })
+}
+
 pub(crate) fn generate(input: DeriveInput) -> syn::Result<TokenStream> {
     let crate_path = crate_path();
 
@@ -157,6 +214,12 @@ fn generate_struct(
         display: type_display,
         context_vis,
         mod_vis,
+        public,
+        level,
+        max_size,
+        lazy,
+        serialize,
+        deserialize,
     } = type_attr;
 
     let display_tokens = match type_display {
@@ -165,19 +228,43 @@ fn generate_struct(
     };
 
     let mut all_field_idents: Vec<&Ident> = Vec::with_capacity(fields.len());
+    let mut redacted_field_idents: Vec<&Ident> = Vec::new();
+    let mut serde_fields: Vec<&Field> = Vec::with_capacity(fields.len());
+    let mut serde_redacted_fields: Vec<&Field> = Vec::new();
     let mut no_source_no_backtrace_field_idents: Vec<&Ident> = Vec::with_capacity(fields.len());
     let mut no_source_no_backtrace_field_generics: Vec<Ident> = Vec::with_capacity(fields.len());
     let mut no_source_no_backtrace_inferred_bounds = InferredBounds::with_capacity(fields.len());
     let mut source_field: Option<&Field> = None;
+    let mut sources_field: Option<&Field> = None;
     let mut backtrace_field: Option<&Field> = None;
 
     for (i, field) in fields.iter().enumerate() {
         let ident = field.ident.as_ref().unwrap();
+        let FieldAttr { redact, sources } = parse_field_attr(&field.attrs)?;
 
         all_field_idents.push(ident);
 
+        let is_special = ident == "source" || sources || ident == "backtrace";
+
+        if redact {
+            if is_special {
+                return Err(syn::Error::new(ident.span(), REDACT_ON_SPECIAL_FIELD));
+            }
+
+            redacted_field_idents.push(ident);
+            serde_redacted_fields.push(field);
+        } else if !is_special {
+            serde_fields.push(field);
+        }
+
         if ident == "source" {
             source_field = Some(field);
+        } else if sources {
+            if sources_field.is_some() {
+                return Err(syn::Error::new(ident.span(), MULTIPLE_SOURCES_FIELDS));
+            }
+
+            sources_field = Some(field);
         } else if ident == "backtrace" {
             backtrace_field = Some(field);
         } else {
@@ -194,24 +281,54 @@ fn generate_struct(
     let middle_type: Type;
     let backtrace_field_tokens: TokenStream;
 
-    match (source_field, backtrace_field) {
+    match (source_field, sources_field, backtrace_field) {
         // incorrect definition
-        (None, None) => {
+        (None, None, None) => {
             return Err(syn::Error::new(
                 struct_ident.span(),
                 incorrect_def(ContextKind::Struct),
             ));
         }
+        (Some(_), Some(sources_field), _) => {
+            return Err(syn::Error::new(sources_field.span(), SOURCE_AND_SOURCES));
+        }
+        (None, Some(sources_field), None) => {
+            return Err(syn::Error::new(
+                sources_field.span(),
+                SOURCES_WITHOUT_BACKTRACE,
+            ));
+        }
         // root error
-        (None, Some(_)) => {
+        (None, None, Some(_)) => {
             error_kind = ErrorKind::Root;
             middle_type = parse_quote! { () };
             backtrace_field_tokens = quote! {
-                backtrace: #crate_path::Backtrace::new(),
+                backtrace: ::core::convert::Into::into(#crate_path::Backtrace::new()),
+            };
+        }
+        // aggregate error
+        (None, Some(sources_field), Some(_)) => {
+            let Some(ty) = vec_element_type(&sources_field.ty) else {
+                return Err(syn::Error::new(
+                    sources_field.ty.span(),
+                    SOURCES_MUST_BE_VEC,
+                ));
+            };
+
+            let sources_ident = sources_field.ident.as_ref().unwrap();
+
+            error_kind = ErrorKind::Multi;
+            middle_type = parse_quote! { #crate_path::Errors<#ty> };
+            backtrace_field_tokens = quote! {
+                #sources_ident: __sources,
+                backtrace: ::core::convert::Into::into(__backtrace),
             };
+            if scope.intersects(ty) {
+                error_inferred_bounds.insert(ty, quote! { #crate_path::Error2 + 'static });
+            }
         }
         // error2 error
-        (Some(source_field), None) => {
+        (Some(source_field), None, None) => {
             let ty = &source_field.ty;
 
             error_kind = ErrorKind::Err2;
@@ -222,13 +339,13 @@ fn generate_struct(
             }
         }
         // std error
-        (Some(source_field), Some(_backtrace_field)) => {
+        (Some(source_field), None, Some(_backtrace_field)) => {
             let ty = &source_field.ty;
 
             error_kind = ErrorKind::Std;
             middle_type = ty.clone();
             backtrace_field_tokens = quote! {
-                backtrace: #crate_path::Backtrace::with_head(&middle),
+                backtrace: ::core::convert::Into::into(#crate_path::Backtrace::with_lazy_head(&middle)),
             };
             if scope.intersects(ty) {
                 error_inferred_bounds.insert(ty, quote! { ::core::error::Error + 'static });
@@ -236,34 +353,76 @@ fn generate_struct(
         }
     }
 
-    let error_source_body = if error_kind.is_root() {
+    let error_source_body = if error_kind.has_source() {
         quote! {
-            ::core::option::Option::None
+            ::core::option::Option::Some(&self.source)
         }
     } else {
         quote! {
-            ::core::option::Option::Some(&self.source)
+            ::core::option::Option::None
         }
     };
 
     let backtrace_body = match error_kind {
-        ErrorKind::Root | ErrorKind::Std => quote! {
-            &self.backtrace
+        ErrorKind::Root | ErrorKind::Std | ErrorKind::Multi => quote! {
+            #crate_path::resolve_displays(
+                self,
+                ::core::borrow::Borrow::<#crate_path::Backtrace>::borrow(&self.backtrace),
+            )
         },
         ErrorKind::Err2 => quote! {
-            #crate_path::Error2::backtrace(&self.source)
+            #crate_path::resolve_displays(self, #crate_path::Error2::backtrace(&self.source))
         },
     };
 
     let backtrace_mut_body = match error_kind {
-        ErrorKind::Root | ErrorKind::Std => quote! {
-            &mut self.backtrace
+        ErrorKind::Root | ErrorKind::Std | ErrorKind::Multi => quote! {
+            ::core::borrow::BorrowMut::<#crate_path::Backtrace>::borrow_mut(&mut self.backtrace)
         },
         ErrorKind::Err2 => quote! {
             #crate_path::Error2::backtrace_mut(&mut self.source)
         },
     };
 
+    let has_redacted_fields = !redacted_field_idents.is_empty();
+
+    let display_depth_body = match error_kind {
+        // rendered when the error is created, unless the type opts into `lazy`
+        _ if !lazy => quote! {
+            ::core::option::Option::None
+        },
+        // rendered when the error is created, under the reveal policy of that time
+        _ if has_redacted_fields => quote! {
+            ::core::option::Option::None
+        },
+        ErrorKind::Root => quote! {
+            ::core::option::Option::Some(1)
+        },
+        ErrorKind::Std | ErrorKind::Multi => quote! {
+            ::core::option::Option::Some(2)
+        },
+        ErrorKind::Err2 => quote! {
+            ::core::option::Option::map(
+                #crate_path::Error2::display_depth(&self.source),
+                |depth| depth + 1,
+            )
+        },
+    };
+
+    let collect_displays_body = match error_kind {
+        ErrorKind::Root => quote! {},
+        // the message of the consumed `Errors`, resolved when it was converted
+        ErrorKind::Multi => quote! {
+            displays.push(&"");
+        },
+        ErrorKind::Std => quote! {
+            displays.push(&self.source);
+        },
+        ErrorKind::Err2 => quote! {
+            #crate_path::Error2::collect_displays(&self.source, displays);
+        },
+    };
+
     let context_def = generate_context_def(
         crate_path,
         &struct_ident,
@@ -289,12 +448,66 @@ fn generate_struct(
                     #[allow(unused_variables)]
                     #[allow(unused_assignments)]
                     let Self { #(#all_field_idents,)* } = self;
+                    #(
+                        #[allow(unused_variables)]
+                        let #redacted_field_idents = #crate_path::render::Redacted(#redacted_field_idents);
+                    )*
                     write!(f, #tokens)
                 }
             }
         },
     };
 
+    let public_message_fn = match public {
+        None => quote! {},
+        Some(public) => quote! {
+            #[inline]
+            fn public_message(&self) -> ::core::option::Option<&'static str> {
+                ::core::option::Option::Some(#public)
+            }
+        },
+    };
+
+    let event_level_fn = match level {
+        None => quote! {},
+        Some(level) => quote! {
+            #[inline]
+            fn event_level(&self) -> ::core::option::Option<#crate_path::trace::Level> {
+                ::core::option::Option::Some(#crate_path::trace::Level::#level)
+            }
+        },
+    };
+
+    let size_assertion = generate_size_assertion(&struct_ident, generics, max_size)?;
+
+    let shapes = [SerdeShape {
+        variant_ident: None,
+        error_kind,
+        fields: serde_fields,
+        redacted_fields: serde_redacted_fields,
+        source_field: source_field.or(sources_field),
+        backtrace_field,
+    }];
+
+    let serialize_impl = if serialize {
+        generate_serialize(
+            crate_path,
+            &struct_ident,
+            generics,
+            scope,
+            &error_inferred_bounds,
+            &shapes,
+        )
+    } else {
+        quote! {}
+    };
+
+    let deserialize_impl = if deserialize {
+        generate_deserialize(crate_path, &struct_ident, generics, scope, &shapes)
+    } else {
+        quote! {}
+    };
+
     let error_where_clause = error_inferred_bounds.augment_where_clause(where_clause.cloned());
 
     let expand = quote! {
@@ -302,6 +515,12 @@ fn generate_struct(
 
         #display_impl
 
+        #size_assertion
+
+        #serialize_impl
+
+        #deserialize_impl
+
         impl #impl_generics ::core::error::Error for #struct_ident #ty_generics #error_where_clause {
             fn source(&self) -> ::core::option::Option<&(dyn ::core::error::Error + 'static)> {
                 #error_source_body
@@ -318,6 +537,24 @@ fn generate_struct(
             fn backtrace_mut(&mut self) -> &mut #crate_path::Backtrace {
                 #backtrace_mut_body
             }
+
+            #public_message_fn
+
+            #event_level_fn
+
+            #[inline]
+            fn display_depth(&self) -> ::core::option::Option<usize> {
+                #display_depth_body
+            }
+
+            #[inline]
+            fn collect_displays<'__a>(
+                &'__a self,
+                displays: &mut #crate_path::Displays<'__a>,
+            ) {
+                #collect_displays_body
+                displays.push(self);
+            }
         }
     };
 
@@ -352,6 +589,12 @@ fn generate_enum(
         display: type_display,
         context_vis,
         mod_vis,
+        public: type_public,
+        level: type_level,
+        max_size,
+        lazy,
+        serialize,
+        deserialize,
     } = type_attr;
 
     if let TypeDisplayAttr::Enabled { meta_span, .. } = type_display {
@@ -361,7 +604,10 @@ fn generate_enum(
     let mut errors = Vec::new();
 
     let mut inputs: Vec<VariantInput> = Vec::with_capacity(variants.len());
+    let mut shapes: Vec<SerdeShape> = Vec::with_capacity(variants.len());
     let mut exist_display_on_variant = false;
+    let mut exist_public = type_public.is_some();
+    let mut exist_level = type_level.is_some();
 
     for variant in &variants {
         let MyVariant {
@@ -372,6 +618,8 @@ fn generate_enum(
 
         let VariantAttr {
             display: variant_display,
+            public: variant_public,
+            level: variant_level,
         } = match parse_variant_attr(variant_attrs) {
             Ok(v) => v,
             Err(e) => {
@@ -384,7 +632,18 @@ fn generate_enum(
             exist_display_on_variant = true;
         }
 
+        if variant_public.is_some() {
+            exist_public = true;
+        }
+
+        if variant_level.is_some() {
+            exist_level = true;
+        }
+
         let mut all_field_idents: Vec<&Ident> = Vec::with_capacity(named_fields.len());
+        let mut redacted_field_idents: Vec<&Ident> = Vec::new();
+        let mut serde_fields: Vec<&Field> = Vec::with_capacity(named_fields.len());
+        let mut serde_redacted_fields: Vec<&Field> = Vec::new();
         let mut no_source_no_backtrace_field_idents: Vec<&Ident> =
             Vec::with_capacity(named_fields.len());
         let mut no_source_no_backtrace_field_generics: Vec<Ident> =
@@ -392,6 +651,7 @@ fn generate_enum(
         let mut no_source_no_backtrace_inferred_bounds =
             InferredBounds::with_capacity(named_fields.len());
         let mut source_field: Option<&Field> = None;
+        let mut sources_field: Option<&Field> = None;
         let mut backtrace_field: Option<&Field> = None;
 
         for (i, field) in named_fields.iter().enumerate() {
@@ -399,8 +659,35 @@ fn generate_enum(
 
             all_field_idents.push(ident);
 
+            let (redact, sources) = match parse_field_attr(&field.attrs) {
+                Ok(FieldAttr { redact, sources }) => (redact, sources),
+                Err(e) => {
+                    errors.push(e);
+                    (false, false)
+                }
+            };
+
+            let is_special = ident == "source" || sources || ident == "backtrace";
+
+            if redact {
+                if is_special {
+                    errors.push(syn::Error::new(ident.span(), REDACT_ON_SPECIAL_FIELD));
+                } else {
+                    redacted_field_idents.push(ident);
+                    serde_redacted_fields.push(field);
+                }
+            } else if !is_special {
+                serde_fields.push(field);
+            }
+
             if ident == "source" {
                 source_field = Some(field);
+            } else if sources {
+                if sources_field.is_some() {
+                    errors.push(syn::Error::new(ident.span(), MULTIPLE_SOURCES_FIELDS));
+                }
+
+                sources_field = Some(field);
             } else if ident == "backtrace" {
                 backtrace_field = Some(field);
             } else {
@@ -417,25 +704,58 @@ fn generate_enum(
         let middle_type: Type;
         let backtrace_field_tokens: TokenStream;
 
-        match (source_field, backtrace_field) {
+        match (source_field, sources_field, backtrace_field) {
             // incorrect definition
-            (None, None) => {
+            (None, None, None) => {
                 errors.push(syn::Error::new(
                     variant_ident.span(),
                     incorrect_def(ContextKind::Variant),
                 ));
                 continue;
             }
+            (Some(_), Some(sources_field), _) => {
+                errors.push(syn::Error::new(sources_field.span(), SOURCE_AND_SOURCES));
+                continue;
+            }
+            (None, Some(sources_field), None) => {
+                errors.push(syn::Error::new(
+                    sources_field.span(),
+                    SOURCES_WITHOUT_BACKTRACE,
+                ));
+                continue;
+            }
             // root error
-            (None, Some(_)) => {
+            (None, None, Some(_)) => {
                 error_kind = ErrorKind::Root;
                 middle_type = parse_quote! { () };
                 backtrace_field_tokens = quote! {
-                    backtrace: #crate_path::Backtrace::new(),
+                    backtrace: ::core::convert::Into::into(#crate_path::Backtrace::new()),
+                };
+            }
+            // aggregate error
+            (None, Some(sources_field), Some(_)) => {
+                let Some(ty) = vec_element_type(&sources_field.ty) else {
+                    errors.push(syn::Error::new(
+                        sources_field.ty.span(),
+                        SOURCES_MUST_BE_VEC,
+                    ));
+                    continue;
+                };
+
+                let sources_ident = sources_field.ident.as_ref().unwrap();
+
+                error_kind = ErrorKind::Multi;
+                middle_type = parse_quote! { #crate_path::Errors<#ty> };
+                backtrace_field_tokens = quote! {
+                    #sources_ident: __sources,
+                    backtrace: ::core::convert::Into::into(__backtrace),
                 };
+                if scope.intersects(ty) {
+                    error_inferred_bounds.insert(ty, quote! { #crate_path::Error2 + 'static });
+                }
             }
             // error2 error
-            (Some(source_field), None) => {
+            (Some(source_field), None, None) => {
                 let ty = &source_field.ty;
 
                 error_kind = ErrorKind::Err2;
@@ -446,13 +766,13 @@ fn generate_enum(
                 }
             }
             // std error
-            (Some(source_field), Some(_backtrace_field)) => {
+            (Some(source_field), None, Some(_backtrace_field)) => {
                 let ty = &source_field.ty;
 
                 error_kind = ErrorKind::Std;
                 middle_type = ty.clone();
                 backtrace_field_tokens = quote! {
-                    backtrace: #crate_path::Backtrace::with_head(&middle),
+                    backtrace: ::core::convert::Into::into(#crate_path::Backtrace::with_lazy_head(&middle)),
                 };
                 if scope.intersects(ty) {
                     error_inferred_bounds.insert(ty, quote! { ::core::error::Error + 'static });
@@ -460,16 +780,28 @@ fn generate_enum(
             }
         }
 
+        shapes.push(SerdeShape {
+            variant_ident: Some(variant_ident),
+            error_kind,
+            fields: serde_fields,
+            redacted_fields: serde_redacted_fields,
+            source_field: source_field.or(sources_field),
+            backtrace_field,
+        });
+
         inputs.push(VariantInput {
             variant_ident,
             error_kind,
             all_field_idents,
+            redacted_field_idents,
             no_source_no_backtrace_field_idents,
             no_source_no_backtrace_field_generics,
             no_source_no_backtrace_inferred_bounds,
             middle_type,
             backtrace_field_tokens,
             variant_display,
+            variant_public,
+            variant_level,
         });
     }
 
@@ -478,18 +810,25 @@ fn generate_enum(
     let mut error_source_arms = Vec::with_capacity(variants.len());
     let mut backtrace_arms = Vec::with_capacity(variants.len());
     let mut backtrace_mut_arms = Vec::with_capacity(variants.len());
+    let mut display_depth_arms = Vec::with_capacity(variants.len());
+    let mut collect_displays_arms = Vec::with_capacity(variants.len());
+    let mut public_message_arms = Vec::with_capacity(variants.len());
+    let mut event_level_arms = Vec::with_capacity(variants.len());
 
     for input in inputs {
         let VariantInput {
             variant_ident,
             error_kind,
             all_field_idents,
+            redacted_field_idents,
             no_source_no_backtrace_field_idents,
             no_source_no_backtrace_field_generics,
             no_source_no_backtrace_inferred_bounds,
             middle_type,
             backtrace_field_tokens,
             variant_display,
+            variant_public,
+            variant_level,
         } = input;
 
         if variant_display.is_none() && exist_display_on_variant {
@@ -506,6 +845,8 @@ fn generate_enum(
             error_source_arm,
             backtrace_arm,
             backtrace_mut_arm,
+            display_depth_arm,
+            collect_displays_arm,
         } = generate_variant(
             crate_path,
             &enum_ident,
@@ -513,6 +854,7 @@ fn generate_enum(
             &context_vis,
             error_kind,
             all_field_idents,
+            redacted_field_idents,
             no_source_no_backtrace_field_idents,
             no_source_no_backtrace_field_generics,
             no_source_no_backtrace_inferred_bounds.merge(&error_inferred_bounds),
@@ -520,6 +862,7 @@ fn generate_enum(
             middle_type,
             backtrace_field_tokens,
             variant_display,
+            lazy,
         );
 
         context_defs.push(context_def);
@@ -527,6 +870,28 @@ fn generate_enum(
         error_source_arms.push(error_source_arm);
         backtrace_arms.push(backtrace_arm);
         backtrace_mut_arms.push(backtrace_mut_arm);
+        display_depth_arms.push(display_depth_arm);
+        collect_displays_arms.push(collect_displays_arm);
+
+        let public_message_arm = match variant_public.as_ref().or(type_public.as_ref()) {
+            None => quote! {
+                Self::#variant_ident { .. } => ::core::option::Option::None,
+            },
+            Some(public) => quote! {
+                Self::#variant_ident { .. } => ::core::option::Option::Some(#public),
+            },
+        };
+        public_message_arms.push(public_message_arm);
+
+        let event_level_arm = match variant_level.as_ref().or(type_level.as_ref()) {
+            None => quote! {
+                Self::#variant_ident { .. } => ::core::option::Option::None,
+            },
+            Some(level) => quote! {
+                Self::#variant_ident { .. } => ::core::option::Option::Some(#crate_path::trace::Level::#level),
+            },
+        };
+        event_level_arms.push(event_level_arm);
     }
 
     if let Some(e) = errors.into_iter().reduce(|mut a, b| {
@@ -554,6 +919,53 @@ fn generate_enum(
         }
     };
 
+    let public_message_fn = if !exist_public {
+        quote! {}
+    } else {
+        quote! {
+            #[inline]
+            fn public_message(&self) -> ::core::option::Option<&'static str> {
+                match self {
+                    #(#public_message_arms)*
+                }
+            }
+        }
+    };
+
+    let event_level_fn = if !exist_level {
+        quote! {}
+    } else {
+        quote! {
+            #[inline]
+            fn event_level(&self) -> ::core::option::Option<#crate_path::trace::Level> {
+                match self {
+                    #(#event_level_arms)*
+                }
+            }
+        }
+    };
+
+    let size_assertion = generate_size_assertion(&enum_ident, generics, max_size)?;
+
+    let serialize_impl = if serialize {
+        generate_serialize(
+            crate_path,
+            &enum_ident,
+            generics,
+            scope,
+            &error_inferred_bounds,
+            &shapes,
+        )
+    } else {
+        quote! {}
+    };
+
+    let deserialize_impl = if deserialize {
+        generate_deserialize(crate_path, &enum_ident, generics, scope, &shapes)
+    } else {
+        quote! {}
+    };
+
     let error_where_clause = error_inferred_bounds.augment_where_clause(where_clause.cloned());
 
     let expand = quote! {
@@ -561,6 +973,12 @@ fn generate_enum(
 
         #display_impl
 
+        #size_assertion
+
+        #serialize_impl
+
+        #deserialize_impl
+
         impl #impl_generics ::core::error::Error for #enum_ident #ty_generics #error_where_clause {
             fn source(&self) -> ::core::option::Option<&(dyn ::core::error::Error + 'static)> {
                 match self {
@@ -572,9 +990,11 @@ fn generate_enum(
         impl #impl_generics #crate_path::Error2 for #enum_ident #ty_generics #error_where_clause {
             #[inline]
             fn backtrace(&self) -> &#crate_path::Backtrace {
-                match self {
+                let backtrace = match self {
                     #(#backtrace_arms)*
-                }
+                };
+
+                #crate_path::resolve_displays(self, backtrace)
             }
 
             #[inline]
@@ -583,6 +1003,28 @@ fn generate_enum(
                     #(#backtrace_mut_arms)*
                 }
             }
+
+            #public_message_fn
+
+            #event_level_fn
+
+            #[inline]
+            fn display_depth(&self) -> ::core::option::Option<usize> {
+                match self {
+                    #(#display_depth_arms)*
+                }
+            }
+
+            #[inline]
+            fn collect_displays<'__a>(
+                &'__a self,
+                displays: &mut #crate_path::Displays<'__a>,
+            ) {
+                match self {
+                    #(#collect_displays_arms)*
+                }
+                displays.push(self);
+            }
         }
     };
 
@@ -612,6 +1054,7 @@ fn generate_variant(
     vis: &Visibility,
     error_kind: ErrorKind,
     all_field_idents: Vec<&Ident>,
+    redacted_field_idents: Vec<&Ident>,
     no_source_no_backtrace_field_idents: Vec<&Ident>,
     no_source_no_backtrace_field_generics: Vec<Ident>,
     all_inferred_bounds: InferredBounds,
@@ -619,7 +1062,10 @@ fn generate_variant(
     middle_type: Type,
     backtrace_field_tokens: TokenStream,
     display_tokens: Option<TokenStream>,
+    lazy: bool,
 ) -> VariantOutput {
+    let has_redacted_fields = !redacted_field_idents.is_empty();
+
     let context_def = generate_context_def(
         crate_path,
         enum_ident,
@@ -639,24 +1085,27 @@ fn generate_variant(
         None => quote! {},
         Some(tokens) => quote! {
             Self::#variant_ident { #(#all_field_idents,)* } => {
+                #(let #redacted_field_idents = #crate_path::render::Redacted(#redacted_field_idents);)*
                 write!(f, #tokens)
             }
         },
     };
 
-    let error_source_arm = if error_kind.is_root() {
+    let error_source_arm = if error_kind.has_source() {
         quote! {
-            Self::#variant_ident { .. } => ::core::option::Option::None,
+            Self::#variant_ident { source, .. } => ::core::option::Option::Some(source),
         }
     } else {
         quote! {
-            Self::#variant_ident { source, .. } => ::core::option::Option::Some(source),
+            Self::#variant_ident { .. } => ::core::option::Option::None,
         }
     };
 
     let backtrace_arm = match error_kind {
-        ErrorKind::Root | ErrorKind::Std => quote! {
-            Self::#variant_ident { backtrace, .. } => backtrace,
+        ErrorKind::Root | ErrorKind::Std | ErrorKind::Multi => quote! {
+            Self::#variant_ident { backtrace, .. } => {
+                ::core::borrow::Borrow::<#crate_path::Backtrace>::borrow(backtrace)
+            }
         },
         ErrorKind::Err2 => quote! {
             Self::#variant_ident { source, .. } => #crate_path::Error2::backtrace(source),
@@ -664,20 +1113,63 @@ fn generate_variant(
     };
 
     let backtrace_mut_arm = match error_kind {
-        ErrorKind::Root | ErrorKind::Std => quote! {
-            Self::#variant_ident { backtrace, .. } => backtrace,
+        ErrorKind::Root | ErrorKind::Std | ErrorKind::Multi => quote! {
+            Self::#variant_ident { backtrace, .. } => {
+                ::core::borrow::BorrowMut::<#crate_path::Backtrace>::borrow_mut(backtrace)
+            }
         },
         ErrorKind::Err2 => quote! {
             Self::#variant_ident { source, .. } => #crate_path::Error2::backtrace_mut(source),
         },
     };
 
+    let display_depth_arm = match error_kind {
+        // rendered when the error is created, unless the type opts into `lazy`
+        _ if !lazy => quote! {
+            Self::#variant_ident { .. } => ::core::option::Option::None,
+        },
+        // rendered when the error is created, under the reveal policy of that time
+        _ if has_redacted_fields => quote! {
+            Self::#variant_ident { .. } => ::core::option::Option::None,
+        },
+        ErrorKind::Root => quote! {
+            Self::#variant_ident { .. } => ::core::option::Option::Some(1),
+        },
+        ErrorKind::Std | ErrorKind::Multi => quote! {
+            Self::#variant_ident { .. } => ::core::option::Option::Some(2),
+        },
+        ErrorKind::Err2 => quote! {
+            Self::#variant_ident { source, .. } => ::core::option::Option::map(
+                #crate_path::Error2::display_depth(source),
+                |depth| depth + 1,
+            ),
+        },
+    };
+
+    let collect_displays_arm = match error_kind {
+        ErrorKind::Root => quote! {
+            Self::#variant_ident { .. } => {}
+        },
+        // the message of the consumed `Errors`, resolved when it was converted
+        ErrorKind::Multi => quote! {
+            Self::#variant_ident { .. } => displays.push(&""),
+        },
+        ErrorKind::Std => quote! {
+            Self::#variant_ident { source, .. } => displays.push(source),
+        },
+        ErrorKind::Err2 => quote! {
+            Self::#variant_ident { source, .. } => #crate_path::Error2::collect_displays(source, displays),
+        },
+    };
+
     VariantOutput {
         context_def,
         display_arm,
         error_source_arm,
         backtrace_arm,
         backtrace_mut_arm,
+        display_depth_arm,
+        collect_displays_arm,
     }
 }
 
@@ -685,12 +1177,15 @@ struct VariantInput<'a> {
     variant_ident: &'a Ident,
     error_kind: ErrorKind,
     all_field_idents: Vec<&'a Ident>,
+    redacted_field_idents: Vec<&'a Ident>,
     no_source_no_backtrace_field_idents: Vec<&'a Ident>,
     no_source_no_backtrace_field_generics: Vec<Ident>,
     no_source_no_backtrace_inferred_bounds: InferredBounds,
     middle_type: Type,
     backtrace_field_tokens: TokenStream,
     variant_display: Option<TokenStream>,
+    variant_public: Option<LitStr>,
+    variant_level: Option<Ident>,
 }
 
 struct VariantOutput {
@@ -699,6 +1194,8 @@ struct VariantOutput {
     error_source_arm: TokenStream,
     backtrace_arm: TokenStream,
     backtrace_mut_arm: TokenStream,
+    display_depth_arm: TokenStream,
+    collect_displays_arm: TokenStream,
 }
 
 #[expect(clippy::too_many_arguments)]
@@ -762,12 +1259,34 @@ fn generate_context_def(
 
     let where_clause = all_inferred_bounds.augment_where_clause(where_clause.cloned());
 
-    let source_field = if error_kind.is_root() {
-        quote! {}
-    } else {
-        quote! {
-            source: middle,
-        }
+    let (prepare_sources, source_field, push_error) = match error_kind {
+        ErrorKind::Root => (
+            quote! {},
+            quote! {},
+            quote! {
+                #crate_path::push_error(&mut error, location);
+            },
+        ),
+        ErrorKind::Std | ErrorKind::Err2 => (
+            quote! {},
+            quote! {
+                source: middle,
+            },
+            quote! {
+                #crate_path::push_error(&mut error, location);
+            },
+        ),
+        // the `sources` field is set with the backtrace field, which continues
+        // the backtrace of the `Errors`, where the sources are recorded
+        ErrorKind::Multi => (
+            quote! {
+                let (__sources, __backtrace) = #crate_path::Errors::into_parts(middle);
+            },
+            quote! {},
+            quote! {
+                #crate_path::push_error(&mut error, location);
+            },
+        ),
     };
 
     quote! {
@@ -777,6 +1296,8 @@ fn generate_context_def(
         impl #additional_impl_generics #crate_path::transform::MiddleToTarget < #middle_type, #type_ident #ty_generics > for #context_ident #context_generics #where_clause {
             #[allow(unused_variables)]
             fn middle_to_target(self, middle: #middle_type, location: #crate_path::Location) -> #type_ident #ty_generics {
+                #prepare_sources
+
                 let mut error = #type_path {
                     #(
                         #no_source_no_backtrace_field_idents : ::core::convert::Into::into(self.#no_source_no_backtrace_field_idents),
@@ -785,7 +1306,7 @@ fn generate_context_def(
                     #source_field
                 };
 
-                #crate_path::push_error(&mut error, location);
+                #push_error
 
                 error
             }
@@ -24,6 +24,7 @@ mod error2;
 mod generics;
 mod messages;
 mod parser;
+mod serialize;
 mod types;
 
 use proc_macro::TokenStream;
@@ -115,6 +116,223 @@ use syn::{DeriveInput, parse_macro_input};
 /// # }
 /// ```
 ///
+/// ### `public`
+///
+/// A user-facing message that is safe to show to end users, unlike the display
+/// message which may contain file paths, SQL or host names. For enums, it is the
+/// default for variants without their own `public` attribute.
+///
+/// ```
+/// # use error2::prelude::*;
+/// #[derive(Debug, Error2)]
+/// #[error2(display("query failed: {sql}"), public = "Could not load your profile")]
+/// pub struct QueryError {
+///     sql: String,
+///     backtrace: Backtrace,
+/// }
+///
+/// let err = QueryError2 { sql: "SELECT *" }.build();
+/// assert_eq!(err.user_message(), "Could not load your profile");
+/// ```
+///
+/// ### `level`
+///
+/// The level of the `tracing` events emitted when the error is created and each
+/// time it is attached, one of `"trace"`, `"debug"`, `"info"`, `"warn"` or
+/// `"error"`. Requires the `tracing` feature of `error2`. For enums, it is the
+/// default for variants without their own `level` attribute.
+///
+/// ```
+/// # use error2::prelude::*;
+/// use error2::trace::Level;
+///
+/// #[derive(Debug, Error2)]
+/// #[error2(display("rate limited"), level = "warn")]
+/// pub struct RateLimited {
+///     backtrace: Backtrace,
+/// }
+///
+/// assert_eq!(RateLimited2.build().event_level(), Some(Level::WARN));
+/// ```
+///
+/// ### `max_size`
+///
+/// Emits a compile-time assertion that the error type is at most this many
+/// bytes, so that it does not silently bloat every `Result` carrying it. Only
+/// applicable to non-generic types.
+///
+/// ```
+/// # use error2::prelude::*;
+/// #[derive(Debug, Error2)]
+/// #[error2(max_size = 32)]
+/// pub enum AppError {
+///     #[error2(display("IO error"))]
+///     Io {
+///         source: Box<std::io::Error>,
+///         backtrace: Box<Backtrace>,
+///     },
+///
+///     #[error2(display("invalid id: {id}"))]
+///     InvalidId { id: u64, backtrace: Box<Backtrace> },
+/// }
+/// ```
+///
+/// ```compile_fail
+/// # use error2::prelude::*;
+/// #[derive(Debug, Error2)]
+/// #[error2(display("too large"), max_size = 16)]
+/// pub struct LargeError {
+///     data: [u8; 64],
+///     backtrace: Backtrace,
+/// }
+/// ```
+///
+/// ### `lazy`
+///
+/// Defers rendering the display message of the error until its backtrace is
+/// first accessed with `backtrace()`, so errors that are matched and discarded
+/// never format it. The message is rendered from the error itself, a backtrace
+/// moved out of the error before that, for example by destructuring it, only
+/// shows the type name. Errors with [`redact`](#redact--sensitive) fields are
+/// always rendered when they are created.
+///
+/// ```
+/// # use error2::prelude::*;
+/// #[derive(Debug, Error2)]
+/// #[error2(display("invalid digit `{digit}`"), lazy)]
+/// pub struct DigitError {
+///     digit: char,
+///     backtrace: Backtrace,
+/// }
+///
+/// let err = DigitError2 { digit: 'x' }.build();
+/// assert!(err.backtrace().error_message().contains("invalid digit `x`"));
+/// ```
+///
+/// ### `serialize` / `deserialize`
+///
+/// Generates `serde::Serialize` or `serde::Deserialize` for the error itself, so
+/// a receiving service sees its structured fields and not only text. Requires
+/// the `serde` feature of `error2`, the `serde` crate is not needed as a direct
+/// dependency.
+///
+/// An error is serialized as a struct with these fields:
+///
+/// - `type_name` - the full type name, such as `my_app::AppError`
+/// - `variant` - the variant name, or `null` for structs
+/// - `display` - the display message
+/// - `fields` - the fields other than `source`, `sources` and `backtrace`, without
+///   [`redact`](#redact--sensitive) fields
+/// - `source` - an error2 source serialized recursively, or the display message of a
+///   std source
+/// - `sources` - the errors of an aggregate error, whatever the name of its field
+/// - `backtrace` - the [`Backtrace`](../error2/struct.Backtrace.html) schema, written
+///   only by the error that owns it
+///
+/// ```
+/// # use error2::prelude::*;
+/// #[derive(Debug, Error2)]
+/// #[error2(serialize)]
+/// pub enum ConfigError {
+///     #[error2(display("failed to read {path}"))]
+///     Read {
+///         path: String,
+///         #[error2(sensitive)]
+///         token: String,
+///         source: std::io::Error,
+///         backtrace: Backtrace,
+///     },
+/// }
+///
+/// let err = std::fs::read_to_string("missing.toml")
+///     .context(Read2 { path: "missing.toml", token: "secret" })
+///     .unwrap_err();
+///
+/// let json = serde_json::to_value(&err).unwrap();
+/// assert_eq!(json["variant"], "Read");
+/// assert_eq!(json["display"], "failed to read missing.toml");
+/// assert_eq!(json["fields"], serde_json::json!({ "path": "missing.toml" }));
+/// assert!(json["source"].is_string());
+/// assert_eq!(json["backtrace"]["version"], 1);
+/// ```
+///
+/// `deserialize` reconstructs the error from the same form, the `display` and
+/// `type_name` fields are ignored. All fields must implement `Deserialize` and
+/// redacted fields are set to their `Default` value. Enums are identified by the
+/// `variant` field, so a self-describing format such as JSON is needed.
+///
+/// ```
+/// # use error2::prelude::*;
+/// #[derive(Debug, Error2)]
+/// #[error2(serialize, deserialize)]
+/// pub enum QuotaError {
+///     #[error2(display("quota of {limit} exceeded by {user}"))]
+///     Exceeded {
+///         user: String,
+///         limit: u32,
+///         backtrace: Backtrace,
+///     },
+/// }
+///
+/// let err = Exceeded2 { user: "alice", limit: 10u32 }.build();
+/// let json = serde_json::to_string(&err).unwrap();
+///
+/// let received: QuotaError = serde_json::from_str(&json).unwrap();
+/// let QuotaError::Exceeded { user, limit, .. } = &received;
+/// assert_eq!((user.as_str(), *limit), ("alice", 10));
+/// assert_eq!(received.backtrace(), err.backtrace());
+/// ```
+///
+/// A std source is deserialized from its display message, so its type must
+/// implement `From<String>`:
+///
+/// ```
+/// # use error2::prelude::*;
+/// #[derive(Debug)]
+/// pub struct PeerMessage(String);
+///
+/// impl From<String> for PeerMessage {
+///     fn from(message: String) -> Self {
+///         Self(message)
+///     }
+/// }
+/// # impl std::fmt::Display for PeerMessage {
+/// #     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+/// #         f.write_str(&self.0)
+/// #     }
+/// # }
+/// # impl std::error::Error for PeerMessage {}
+///
+/// #[derive(Debug, Error2)]
+/// #[error2(display("request to {peer} failed"), serialize, deserialize)]
+/// pub struct RequestError {
+///     peer: String,
+///     source: PeerMessage,
+///     backtrace: Backtrace,
+/// }
+///
+/// let err = Err::<(), _>(PeerMessage("connection reset".to_string()))
+///     .context(RequestError2 { peer: "10.0.0.1" })
+///     .unwrap_err();
+/// let json = serde_json::to_string(&err).unwrap();
+///
+/// let received: RequestError = serde_json::from_str(&json).unwrap();
+/// assert_eq!(received.source.0, "connection reset");
+/// assert_eq!(received.backtrace(), err.backtrace());
+/// ```
+///
+/// Most std errors, such as `std::io::Error`, cannot be built from a message:
+///
+/// ```compile_fail
+/// # use error2::prelude::*;
+/// #[derive(Debug, Error2)]
+/// #[error2(display("failed to read config"), deserialize)]
+/// pub struct ReadError {
+///     source: std::io::Error,
+///     backtrace: Backtrace,
+/// }
+/// ```
+///
 /// ## Variant-Level Attributes
 ///
 /// Applied to enum variants:
@@ -140,6 +358,82 @@ use syn::{DeriveInput, parse_macro_input};
 /// }
 /// ```
 ///
+/// ### `public`
+///
+/// A user-facing message for this variant, overriding the type-level `public`.
+///
+/// ```
+/// # use error2::prelude::*;
+/// #[derive(Debug, Error2)]
+/// #[error2(public = "Something went wrong")]
+/// pub enum ProfileError {
+///     #[error2(display("no row for user {id}"), public = "Could not load your profile")]
+///     Missing { id: u64, backtrace: Backtrace },
+///
+///     #[error2(display("pool exhausted"))]
+///     Pool { backtrace: Backtrace },
+/// }
+///
+/// assert_eq!(Missing2 { id: 7u64 }.build().user_message(), "Could not load your profile");
+/// assert_eq!(Pool2.build().user_message(), "Something went wrong");
+/// ```
+///
+/// ### `level`
+///
+/// The level of the `tracing` events of this variant, overriding the type-level `level`.
+///
+/// ```
+/// # use error2::prelude::*;
+/// use error2::trace::Level;
+///
+/// #[derive(Debug, Error2)]
+/// #[error2(level = "error")]
+/// pub enum CacheError {
+///     #[error2(display("cache miss for {key}"), level = "debug")]
+///     Miss { key: String, backtrace: Backtrace },
+///
+///     #[error2(display("cache unreachable"))]
+///     Unreachable { backtrace: Backtrace },
+/// }
+///
+/// assert_eq!(Miss2 { key: "user:7" }.build().event_level(), Some(Level::DEBUG));
+/// assert_eq!(Unreachable2.build().event_level(), Some(Level::ERROR));
+/// ```
+///
+/// ## Field-Level Attributes
+///
+/// Applied to fields of a struct or enum variant:
+///
+/// ### `redact` / `sensitive`
+///
+/// Masks the field as `***` in the generated `Display`, so tokens, passwords or
+/// personal data do not end up in logs or serialized backtraces. Use
+/// [`set_reveal`](../error2/render/fn.set_reveal.html) to show the values in debug
+/// builds or when explicitly requested.
+///
+/// A derived `Debug` still prints the raw value, unless the field is stored as
+/// [`Redacted<T>`](../error2/render/struct.Redacted.html).
+///
+/// ```
+/// # use error2::prelude::*;
+/// #[derive(Debug, Error2)]
+/// #[error2(display("login failed for {user} with password {password}"))]
+/// pub struct LoginError {
+///     user: String,
+///     #[error2(sensitive)]
+///     password: String,
+///     backtrace: Backtrace,
+/// }
+///
+/// let err = LoginError2 { user: "alice", password: "hunter2" }.build();
+/// assert_eq!(err.to_string(), "login failed for alice with password ***");
+/// ```
+///
+/// ### `sources`
+///
+/// Marks the `Vec<E>` field of an error that aggregates many errors, see
+/// [Aggregating Multiple Errors](#aggregating-multiple-errors).
+///
 /// # Generated Helper Structs
 ///
 /// The macro generates helper structs for type conversion, named by appending `2`:
@@ -171,7 +465,7 @@ use syn::{DeriveInput, parse_macro_input};
 /// // Generates: struct FileError2;
 /// ```
 ///
-/// These helper structs contain only the non-`source` and non-`backtrace` fields.
+/// These helper structs contain only the fields other than `source`, `sources` and `backtrace`.
 /// **All fields are generic with `Into` trait bounds**, allowing automatic type conversion:
 ///
 /// ```
@@ -242,6 +536,85 @@ use syn::{DeriveInput, parse_macro_input};
 /// This works because the helper struct implements `Into<Box<E>>` for `E`.
 /// Any wrapper type that implements `E: Into<Wrapper<E>>` can be used.
 ///
+/// The `backtrace` field can be boxed in the same way, as `Box<Backtrace>` or
+/// [`BoxedBacktrace`](../error2/struct.BoxedBacktrace.html), which is one pointer
+/// wide and only allocates when the first entry is recorded:
+///
+/// ```
+/// # use error2::{BoxedBacktrace, prelude::*};
+/// #[derive(Debug, Error2)]
+/// #[error2(display("invalid port: {port}"))]
+/// struct InvalidPort {
+///     port: u16,
+///     source: std::num::ParseIntError,
+///     backtrace: BoxedBacktrace,
+/// }
+///
+/// assert_eq!(std::mem::size_of::<Result<(), InvalidPort>>(), 16);
+/// ```
+///
+/// # Aggregating Multiple Errors
+///
+/// A `Vec<E>` field marked with `#[error2(sources)]`, together with a `backtrace`
+/// field, defines an error that aggregates many independent `Error2` errors. Its
+/// helper struct converts from [`Errors<E>`](../error2/struct.Errors.html), whose
+/// backtrace it continues, so the location where the errors were collected, its
+/// attachments and every inner error are rendered below the aggregate:
+///
+/// ```
+/// # use error2::prelude::*;
+/// #[derive(Debug, Error2)]
+/// #[error2(display("invalid field `{name}`"))]
+/// struct InvalidField {
+///     name: String,
+///     backtrace: Backtrace,
+/// }
+///
+/// #[derive(Debug, Error2)]
+/// #[error2(display("config has {} invalid fields", fields.len()))]
+/// struct ConfigError {
+///     #[error2(sources)]
+///     fields: Vec<InvalidField>,
+///     backtrace: Backtrace,
+/// }
+///
+/// fn check(name: &str) -> Result<(), InvalidField> {
+///     InvalidField2 { name }.fail()
+/// }
+///
+/// fn validate() -> Result<Vec<()>, ConfigError> {
+///     ["port", "host"]
+///         .into_iter()
+///         .map(check)
+///         .try_collect_all()
+///         .context(ConfigError2)
+/// }
+///
+/// let err = validate().unwrap_err();
+/// assert_eq!(err.fields.len(), 2);
+///
+/// let msg = err.backtrace().error_message();
+/// assert!(msg.contains("ConfigError: config has 2 invalid fields"));
+/// assert!(msg.contains("Errors<"));
+/// assert!(msg.contains("\n    [1] "));
+/// ```
+///
+/// Without the attribute, a field named `sources` is an ordinary field:
+///
+/// ```
+/// # use error2::prelude::*;
+/// #[derive(Debug, Error2)]
+/// #[error2(display("cannot resolve {host} from {}", sources.join(", ")))]
+/// struct ResolveError {
+///     host: String,
+///     sources: Vec<String>,
+///     backtrace: Backtrace,
+/// }
+///
+/// let err = ResolveError2 { host: "db", sources: vec!["dns".to_string()] }.build();
+/// assert_eq!(err.to_string(), "cannot resolve db from dns");
+/// ```
+///
 /// # Display Implementation
 ///
 /// **Important:** The `Display` trait is only implemented when `display` attribute is present.
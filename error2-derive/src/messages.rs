@@ -1,5 +1,3 @@
-use syn::Ident;
-
 use crate::types::ContextKind;
 
 pub(crate) const SUPPORTED_TYPES: &str =
@@ -19,17 +17,46 @@ pub(crate) const VIS_MUST_IN_META_LIST: &str =
 pub(crate) const MODULE_MUST_IN_PATH: &str =
     "`module` attribute can only appear in path, such as `#[error2(module)]`";
 
+pub(crate) const PUBLIC_MUST_BE_STR: &str = "`public` attribute can only appear in name-value form with a string literal, such as `#[error2(public = \"some message\")]`";
+
+pub(crate) const LEVEL_MUST_BE_STR: &str = "`level` attribute can only appear in name-value form with one of \"trace\", \"debug\", \"info\", \"warn\" or \"error\", such as `#[error2(level = \"warn\")]`";
+
+pub(crate) const MAX_SIZE_MUST_BE_INT: &str = "`max_size` attribute can only appear in name-value form with an integer literal, such as `#[error2(max_size = 32)]`";
+
+pub(crate) const LAZY_MUST_IN_PATH: &str =
+    "`lazy` attribute can only appear in path, such as `#[error2(lazy)]`";
+
+pub(crate) const SERIALIZE_MUST_IN_PATH: &str =
+    "`serialize` attribute can only appear in path, such as `#[error2(serialize)]`";
+
+pub(crate) const DESERIALIZE_MUST_IN_PATH: &str =
+    "`deserialize` attribute can only appear in path, such as `#[error2(deserialize)]`";
+
+pub(crate) const MAX_SIZE_ON_GENERIC: &str = "`max_size` attribute cannot be used on generic types";
+
+pub(crate) const REDACT_MUST_IN_PATH: &str = "`redact` attribute can only appear in path, such as `#[error2(redact)]` or `#[error2(sensitive)]`";
+
+pub(crate) const REDACT_ON_SPECIAL_FIELD: &str =
+    "`redact` attribute cannot be used on `source`, `backtrace` or `#[error2(sources)]` fields";
+
+pub(crate) const SOURCES_MUST_IN_PATH: &str =
+    "`sources` attribute can only appear in path, such as `#[error2(sources)]`";
+
 pub(crate) const DISPLAY_TOKENS_NOT_ON_ENUM: &str =
     "enums can only omit the `display` attribute or use `#[error2(display(false))]`";
 
 pub(crate) const MISSING_DISPLAY_ON_VARIANT: &str = "missing `#[error2(display(...))]` attribute";
 
-pub(crate) fn unknown_single_attr(path_ident: &Ident, attr: &'static str) -> String {
-    format!(
-        "unknown attribute `{}`, only `{}` is supported",
-        path_ident, attr
-    )
-}
+pub(crate) const SOURCES_MUST_BE_VEC: &str =
+    "a `#[error2(sources)]` field must be a `Vec<E>`, where `E` implements `Error2`";
+
+pub(crate) const MULTIPLE_SOURCES_FIELDS: &str = "only one field can have the `sources` attribute";
+
+pub(crate) const SOURCE_AND_SOURCES: &str =
+    "`source` and `#[error2(sources)]` fields cannot be used together";
+
+pub(crate) const SOURCES_WITHOUT_BACKTRACE: &str =
+    "a `#[error2(sources)]` field requires a `backtrace` field";
 
 pub(crate) fn specified_multiple_times(attr: &'static str) -> String {
     format!("`{}` attribute specified multiple times", attr)
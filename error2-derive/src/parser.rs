@@ -1,21 +1,119 @@
 use proc_macro2::TokenStream;
 use quote::quote;
-use syn::{Attribute, Meta, Token, Visibility, punctuated::Punctuated, spanned::Spanned};
+use syn::{
+    Attribute, Expr, ExprLit, Ident, Lit, LitInt, LitStr, Meta, MetaNameValue, Token, Visibility,
+    punctuated::Punctuated, spanned::Spanned,
+};
 
 use crate::{
     messages::{
-        DISPLAY_MUST_IN_META_LIST, EXPECTED_IDENT, MODULE_MUST_IN_PATH, VIS_MUST_IN_META_LIST,
-        specified_multiple_times, unknown_single_attr,
+        DESERIALIZE_MUST_IN_PATH, DISPLAY_MUST_IN_META_LIST, EXPECTED_IDENT, LAZY_MUST_IN_PATH,
+        LEVEL_MUST_BE_STR, MAX_SIZE_MUST_BE_INT, MODULE_MUST_IN_PATH, PUBLIC_MUST_BE_STR,
+        REDACT_MUST_IN_PATH, SERIALIZE_MUST_IN_PATH, SOURCES_MUST_IN_PATH, VIS_MUST_IN_META_LIST,
+        specified_multiple_times,
     },
-    types::{TypeAttr, TypeDisplayAttr, VariantAttr},
+    types::{FieldAttr, TypeAttr, TypeDisplayAttr, VariantAttr},
 };
 
+fn parse_flag(
+    meta: Meta,
+    name: &'static str,
+    message: &'static str,
+    flag: &mut bool,
+    errors: &mut Vec<syn::Error>,
+) {
+    let path = match meta {
+        Meta::Path(path) => path,
+        Meta::List(_) | Meta::NameValue(_) => {
+            errors.push(syn::Error::new(meta.span(), message));
+            return;
+        }
+    };
+
+    if *flag {
+        errors.push(syn::Error::new(path.span(), specified_multiple_times(name)));
+        return;
+    }
+
+    *flag = true;
+}
+
+fn parse_public(meta: Meta, public: &mut Option<LitStr>, errors: &mut Vec<syn::Error>) {
+    let lit = match meta {
+        Meta::NameValue(MetaNameValue {
+            value: Expr::Lit(ExprLit {
+                lit: Lit::Str(lit), ..
+            }),
+            ..
+        }) => lit,
+        Meta::Path(_) | Meta::List(_) | Meta::NameValue(_) => {
+            errors.push(syn::Error::new(meta.span(), PUBLIC_MUST_BE_STR));
+            return;
+        }
+    };
+
+    if public.is_some() {
+        errors.push(syn::Error::new(
+            lit.span(),
+            specified_multiple_times("public"),
+        ));
+        return;
+    }
+
+    *public = Some(lit);
+}
+
+/// Parses `level = "warn"` into the name of the `tracing::Level` constant, `WARN`.
+fn parse_level(meta: Meta, level: &mut Option<Ident>, errors: &mut Vec<syn::Error>) {
+    let lit = match meta {
+        Meta::NameValue(MetaNameValue {
+            value: Expr::Lit(ExprLit {
+                lit: Lit::Str(lit), ..
+            }),
+            ..
+        }) => lit,
+        Meta::Path(_) | Meta::List(_) | Meta::NameValue(_) => {
+            errors.push(syn::Error::new(meta.span(), LEVEL_MUST_BE_STR));
+            return;
+        }
+    };
+
+    let name = match lit.value().as_str() {
+        "trace" => "TRACE",
+        "debug" => "DEBUG",
+        "info" => "INFO",
+        "warn" => "WARN",
+        "error" => "ERROR",
+        _ => {
+            errors.push(syn::Error::new(lit.span(), LEVEL_MUST_BE_STR));
+            return;
+        }
+    };
+
+    if level.is_some() {
+        errors.push(syn::Error::new(
+            lit.span(),
+            specified_multiple_times("level"),
+        ));
+        return;
+    }
+
+    *level = Some(Ident::new(name, lit.span()));
+}
+
 pub(crate) fn parse_type_attr(attrs: &[Attribute]) -> syn::Result<TypeAttr> {
+    #[expect(clippy::too_many_arguments)]
     fn inner(
         attr: &Attribute,
         display: &mut TypeDisplayAttr,
         vis: &mut Option<Visibility>,
         module: &mut bool,
+        public: &mut Option<LitStr>,
+        level: &mut Option<Ident>,
+        max_size: &mut Option<LitInt>,
+        lazy: &mut bool,
+        serialize: &mut bool,
+        deserialize: &mut bool,
         errors: &mut Vec<syn::Error>,
     ) {
         if !attr.path().is_ident("error2") {
@@ -100,11 +198,56 @@ pub(crate) fn parse_type_attr(attrs: &[Attribute]) -> syn::Result<TypeAttr> {
                 } else {
                     *module = true;
                 }
+            } else if path_ident == "public" {
+                parse_public(meta, public, errors);
+            } else if path_ident == "level" {
+                parse_level(meta, level, errors);
+            } else if path_ident == "max_size" {
+                let lit = match meta {
+                    Meta::NameValue(MetaNameValue {
+                        value:
+                            Expr::Lit(ExprLit {
+                                lit: Lit::Int(lit), ..
+                            }),
+                        ..
+                    }) => lit,
+                    Meta::Path(_) | Meta::List(_) | Meta::NameValue(_) => {
+                        errors.push(syn::Error::new(meta.span(), MAX_SIZE_MUST_BE_INT));
+                        continue;
+                    }
+                };
+
+                if let Err(e) = lit.base10_parse::<usize>() {
+                    errors.push(e);
+                    continue;
+                }
+
+                if max_size.is_some() {
+                    errors.push(syn::Error::new(
+                        lit.span(),
+                        specified_multiple_times("max_size"),
+                    ));
+                    continue;
+                }
+
+                *max_size = Some(lit);
+            } else if path_ident == "lazy" {
+                parse_flag(meta, "lazy", LAZY_MUST_IN_PATH, lazy, errors);
+            } else if path_ident == "serialize" {
+                parse_flag(meta, "serialize", SERIALIZE_MUST_IN_PATH, serialize, errors);
+            } else if path_ident == "deserialize" {
+                parse_flag(
+                    meta,
+                    "deserialize",
+                    DESERIALIZE_MUST_IN_PATH,
+                    deserialize,
+                    errors,
+                );
             } else {
                 errors.push(syn::Error::new(
                     path_ident.span(),
                     format!(
-                        "unknown attribute `{}`, only `display`, `vis` and `module` are supported",
+                        "unknown attribute `{}`, only `display`, `vis`, `module`, `public`, `level`, `max_size`, `lazy`, `serialize` and `deserialize` are supported",
                         path_ident
                     ),
                 ));
@@ -115,12 +258,30 @@ pub(crate) fn parse_type_attr(attrs: &[Attribute]) -> syn::Result<TypeAttr> {
     let mut display = TypeDisplayAttr::None;
     let mut vis: Option<Visibility> = None;
     let mut module = false;
+    let mut public: Option<LitStr> = None;
+    let mut level: Option<Ident> = None;
+    let mut max_size: Option<LitInt> = None;
+    let mut lazy = false;
+    let mut serialize = false;
+    let mut deserialize = false;
 
     let mut errors = Vec::new();
 
-    attrs
-        .iter()
-        .for_each(|attr| inner(attr, &mut display, &mut vis, &mut module, &mut errors));
+    attrs.iter().for_each(|attr| {
+        inner(
+            attr,
+            &mut display,
+            &mut vis,
+            &mut module,
+            &mut public,
+            &mut level,
+            &mut max_size,
+            &mut lazy,
+            &mut serialize,
+            &mut deserialize,
+            &mut errors,
+        )
+    });
 
     if let Some(e) = errors.into_iter().reduce(|mut a, b| {
         a.combine(b);
@@ -143,11 +304,23 @@ pub(crate) fn parse_type_attr(attrs: &[Attribute]) -> syn::Result<TypeAttr> {
         display,
         context_vis,
         mod_vis,
+        public,
+        level,
+        max_size,
+        lazy,
+        serialize,
+        deserialize,
     })
 }
 
 pub(crate) fn parse_variant_attr(attrs: &[Attribute]) -> syn::Result<VariantAttr> {
-    fn inner(attr: &Attribute, display: &mut Option<TokenStream>, errors: &mut Vec<syn::Error>) {
+    fn inner(
+        attr: &Attribute,
+        display: &mut Option<TokenStream>,
+        public: &mut Option<LitStr>,
+        level: &mut Option<Ident>,
+        errors: &mut Vec<syn::Error>,
+    ) {
         if !attr.path().is_ident("error2") {
             return;
         }
@@ -186,21 +359,111 @@ pub(crate) fn parse_variant_attr(attrs: &[Attribute]) -> syn::Result<VariantAttr
                 }
 
                 *display = Some(list.tokens);
+            } else if path_ident == "public" {
+                parse_public(meta, public, errors);
+            } else if path_ident == "level" {
+                parse_level(meta, level, errors);
             } else {
                 errors.push(syn::Error::new(
                     path_ident.span(),
-                    unknown_single_attr(path_ident, "display"),
+                    format!(
+                        "unknown attribute `{}`, only `display`, `public` and `level` are supported",
+                        path_ident
+                    ),
                 ));
             }
         }
     }
 
     let mut display = None;
+    let mut public = None;
+    let mut level = None;
+    let mut errors = Vec::new();
+
+    attrs
+        .iter()
+        .for_each(|attr| inner(attr, &mut display, &mut public, &mut level, &mut errors));
+
+    if let Some(e) = errors.into_iter().reduce(|mut a, b| {
+        a.combine(b);
+        a
+    }) {
+        return Err(e);
+    }
+
+    Ok(VariantAttr {
+        display,
+        public,
+        level,
+    })
+}
+
+pub(crate) fn parse_field_attr(attrs: &[Attribute]) -> syn::Result<FieldAttr> {
+    fn inner(
+        attr: &Attribute,
+        redact: &mut bool,
+        sources: &mut bool,
+        errors: &mut Vec<syn::Error>,
+    ) {
+        if !attr.path().is_ident("error2") {
+            return;
+        }
+
+        let nested = match attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated) {
+            Ok(o) => o,
+            Err(e) => {
+                errors.push(e);
+                return;
+            }
+        };
+
+        for meta in nested {
+            let path = meta.path();
+
+            let Some(path_ident) = path.get_ident() else {
+                errors.push(syn::Error::new(path.span(), EXPECTED_IDENT));
+                continue;
+            };
+
+            if path_ident == "redact" || path_ident == "sensitive" {
+                let path = match meta {
+                    Meta::Path(path) => path,
+                    Meta::List(_) | Meta::NameValue(_) => {
+                        errors.push(syn::Error::new(meta.span(), REDACT_MUST_IN_PATH));
+                        continue;
+                    }
+                };
+
+                if *redact {
+                    errors.push(syn::Error::new(
+                        path.span(),
+                        specified_multiple_times("redact"),
+                    ));
+                    continue;
+                }
+
+                *redact = true;
+            } else if path_ident == "sources" {
+                parse_flag(meta, "sources", SOURCES_MUST_IN_PATH, sources, errors);
+            } else {
+                errors.push(syn::Error::new(
+                    path_ident.span(),
+                    format!(
+                        "unknown attribute `{}`, only `redact`, `sensitive` and `sources` are supported",
+                        path_ident
+                    ),
+                ));
+            }
+        }
+    }
+
+    let mut redact = false;
+    let mut sources = false;
     let mut errors = Vec::new();
 
     attrs
         .iter()
-        .for_each(|attr| inner(attr, &mut display, &mut errors));
+        .for_each(|attr| inner(attr, &mut redact, &mut sources, &mut errors));
 
     if let Some(e) = errors.into_iter().reduce(|mut a, b| {
         a.combine(b);
@@ -209,5 +472,5 @@ pub(crate) fn parse_variant_attr(attrs: &[Attribute]) -> syn::Result<VariantAttr
         return Err(e);
     }
 
-    Ok(VariantAttr { display })
+    Ok(FieldAttr { redact, sources })
 }
@@ -0,0 +1,364 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::{Field, Generics, Ident, LitStr, parse_quote, spanned::Spanned};
+
+use crate::{
+    generics::{InferredBounds, ParamsInScope},
+    types::{ErrorKind, SerdeShape},
+};
+
+fn serde_path(crate_path: &TokenStream) -> TokenStream {
+    quote! { #crate_path::serialize::serde }
+}
+
+/// The `#[serde(crate = "...")]` attribute of the helper types.
+fn serde_crate_attr(crate_path: &TokenStream) -> TokenStream {
+    let path = LitStr::new(
+        &serde_path(crate_path).to_string(),
+        proc_macro2::Span::call_site(),
+    );
+
+    quote! { #[serde(crate = #path)] }
+}
+
+fn field_ident(field: &Field) -> &Ident {
+    field.ident.as_ref().unwrap()
+}
+
+fn pattern(shape: &SerdeShape<'_>) -> TokenStream {
+    match shape.variant_ident {
+        None => quote! { Self },
+        Some(variant_ident) => quote! { Self::#variant_ident },
+    }
+}
+
+/// A helper struct holding the fields that are neither special nor redacted.
+fn fields_struct(crate_path: &TokenStream, ident: &Ident, fields: &[&Field]) -> TokenStream {
+    let serde_path = serde_path(crate_path);
+    let serde_crate_attr = serde_crate_attr(crate_path);
+
+    let field_idents = fields.iter().map(|field| field_ident(field));
+    let field_generics = (0..fields.len())
+        .map(|i| format_ident!("__F{}", i))
+        .collect::<Vec<_>>();
+
+    quote! {
+        #[derive(#serde_path::Serialize, #serde_path::Deserialize)]
+        #serde_crate_attr
+        struct #ident < #(#field_generics,)* > {
+            #( #field_idents: #field_generics, )*
+        }
+    }
+}
+
+pub(crate) fn generate_serialize(
+    crate_path: &TokenStream,
+    type_ident: &Ident,
+    generics: &Generics,
+    scope: &ParamsInScope,
+    error_inferred_bounds: &InferredBounds,
+    shapes: &[SerdeShape<'_>],
+) -> TokenStream {
+    let serde_path = serde_path(crate_path);
+    let type_name = LitStr::new(&type_ident.to_string(), type_ident.span());
+
+    let mut inferred_bounds = InferredBounds::with_capacity(shapes.len());
+
+    let arms = shapes.iter().enumerate().map(|(i, shape)| {
+        let pattern = pattern(shape);
+        let fields_ident = format_ident!("__Fields{}", i);
+        let fields_struct = fields_struct(crate_path, &fields_ident, &shape.fields);
+        let field_idents = shape.fields.iter().map(|field| field_ident(field)).collect::<Vec<_>>();
+
+        for field in &shape.fields {
+            if scope.intersects(&field.ty) {
+                inferred_bounds.insert(&field.ty, quote! { #serde_path::Serialize });
+            }
+        }
+
+        let variant = match shape.variant_ident {
+            None => quote! { ::core::option::Option::None::<&'static str> },
+            Some(variant_ident) => {
+                let name = LitStr::new(&variant_ident.to_string(), variant_ident.span());
+                quote! { ::core::option::Option::Some(#name) }
+            }
+        };
+
+        let (source_binding, source_field) = match (shape.error_kind, shape.source_field) {
+            (ErrorKind::Std, Some(_)) => (
+                quote! { source, },
+                quote! {
+                    #serde_path::ser::SerializeStruct::serialize_field(
+                        &mut state,
+                        "source",
+                        &#crate_path::serialize::Displayed(source),
+                    )?;
+                },
+            ),
+            (ErrorKind::Err2, Some(field)) => {
+                if scope.intersects(&field.ty) {
+                    inferred_bounds.insert(&field.ty, quote! { #serde_path::Serialize });
+                }
+
+                (
+                    quote! { source, },
+                    quote! {
+                        #serde_path::ser::SerializeStruct::serialize_field(&mut state, "source", source)?;
+                    },
+                )
+            }
+            (ErrorKind::Multi, Some(field)) => {
+                if scope.intersects(&field.ty) {
+                    inferred_bounds.insert(&field.ty, quote! { #serde_path::Serialize });
+                }
+
+                let ident = field_ident(field);
+
+                (
+                    quote! { #ident: __sources, },
+                    quote! {
+                        #serde_path::ser::SerializeStruct::serialize_field(&mut state, "sources", __sources)?;
+                    },
+                )
+            }
+            _ => (quote! {}, quote! {}),
+        };
+
+        // the backtrace is serialized by the error that owns it
+        let backtrace_field = match shape.backtrace_field {
+            None => quote! {},
+            Some(_) => quote! {
+                #serde_path::ser::SerializeStruct::serialize_field(&mut state, "backtrace", backtrace)?;
+            },
+        };
+
+        let len = 4 + usize::from(shape.source_field.is_some()) + usize::from(shape.backtrace_field.is_some());
+
+        quote! {
+            #pattern { #(#field_idents,)* #source_binding .. } => {
+                #fields_struct
+
+                // also resolves the pending display messages of the chain
+                #[allow(unused_variables)]
+                let backtrace = #crate_path::Error2::backtrace(self);
+
+                let mut state = #serde_path::Serializer::serialize_struct(serializer, #type_name, #len)?;
+                #serde_path::ser::SerializeStruct::serialize_field(
+                    &mut state,
+                    "type_name",
+                    ::core::any::type_name::<Self>(),
+                )?;
+                #serde_path::ser::SerializeStruct::serialize_field(&mut state, "variant", &#variant)?;
+                #serde_path::ser::SerializeStruct::serialize_field(
+                    &mut state,
+                    "display",
+                    &#crate_path::serialize::Displayed(self),
+                )?;
+                #serde_path::ser::SerializeStruct::serialize_field(
+                    &mut state,
+                    "fields",
+                    &#fields_ident { #(#field_idents,)* },
+                )?;
+                #source_field
+                #backtrace_field
+                #serde_path::ser::SerializeStruct::end(state)
+            }
+        }
+    }).collect::<Vec<_>>();
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let where_clause = inferred_bounds
+        .merge(error_inferred_bounds)
+        .augment_where_clause(where_clause.cloned());
+
+    quote! {
+        impl #impl_generics #serde_path::Serialize for #type_ident #ty_generics #where_clause {
+            fn serialize<__S>(&self, serializer: __S) -> ::core::result::Result<__S::Ok, __S::Error>
+            where
+                __S: #serde_path::Serializer,
+            {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+    }
+}
+
+pub(crate) fn generate_deserialize(
+    crate_path: &TokenStream,
+    type_ident: &Ident,
+    generics: &Generics,
+    scope: &ParamsInScope,
+    shapes: &[SerdeShape<'_>],
+) -> TokenStream {
+    let serde_path = serde_path(crate_path);
+    let serde_crate_attr = serde_crate_attr(crate_path);
+
+    let mut inferred_bounds = InferredBounds::with_capacity(shapes.len());
+    let mut insert_bound = |field: &Field, bound: TokenStream| {
+        if scope.intersects(&field.ty) {
+            inferred_bounds.insert(&field.ty, bound);
+        }
+    };
+
+    let mut fields_structs = Vec::with_capacity(shapes.len());
+    let mut repr_members = Vec::with_capacity(shapes.len());
+    let mut repr_generics = Vec::new();
+    let mut repr_types = Vec::new();
+    let mut arms = Vec::with_capacity(shapes.len());
+    let mut std_source_bounds = Vec::new();
+
+    for (i, shape) in shapes.iter().enumerate() {
+        let fields_ident = format_ident!("__Fields{}", i);
+        fields_structs.push(fields_struct(crate_path, &fields_ident, &shape.fields));
+
+        let field_idents = shape
+            .fields
+            .iter()
+            .map(|field| field_ident(field))
+            .collect::<Vec<_>>();
+        let field_types = shape.fields.iter().map(|field| &field.ty);
+        let redacted_field_idents = shape.redacted_fields.iter().map(|field| field_ident(field));
+
+        for field in &shape.fields {
+            insert_bound(field, quote! { #serde_path::Deserialize<'__de> });
+        }
+
+        for field in &shape.redacted_fields {
+            insert_bound(field, quote! { ::core::default::Default });
+        }
+
+        let mut members = Vec::with_capacity(3);
+        let mut repr_bindings = Vec::with_capacity(2);
+        let mut bindings = Vec::with_capacity(2);
+
+        let fields_generic = format_ident!("__R{}", repr_generics.len());
+        members.push(quote! { fields: #fields_generic });
+        repr_generics.push(fields_generic);
+        repr_types.push(quote! { #fields_ident < #(#field_types,)* > });
+
+        // `source` or `sources`, then `backtrace`
+        for (field, kind) in [
+            (shape.source_field, Some(shape.error_kind)),
+            (shape.backtrace_field, None),
+        ] {
+            let Some(field) = field else {
+                continue;
+            };
+
+            let ident = field_ident(field);
+            let ty = &field.ty;
+            let generic = format_ident!("__R{}", repr_generics.len());
+
+            match kind {
+                // a std source was serialized as its display message
+                Some(ErrorKind::Std) => {
+                    let repr_ty = quote_spanned! {ty.span()=>
+                        #crate_path::serialize::FromDisplay<#ty>
+                    };
+
+                    if scope.intersects(ty) {
+                        std_source_bounds.push(repr_ty.clone());
+                    }
+
+                    members.push(quote! { #ident: #generic });
+                    repr_bindings.push(quote! { #ident });
+                    bindings.push(quote! { #ident: #ident.0 });
+                    repr_types.push(repr_ty);
+                }
+                // the field of an aggregate error can have any name
+                Some(ErrorKind::Multi) => {
+                    insert_bound(field, quote! { #serde_path::Deserialize<'__de> });
+
+                    members.push(quote! { #[serde(rename = "sources")] __sources: #generic });
+                    repr_bindings.push(quote! { __sources });
+                    bindings.push(quote! { #ident: __sources });
+                    repr_types.push(quote! { #ty });
+                }
+                _ => {
+                    insert_bound(field, quote! { #serde_path::Deserialize<'__de> });
+
+                    members.push(quote! { #ident: #generic });
+                    repr_bindings.push(quote! { #ident });
+                    bindings.push(quote! { #ident });
+                    repr_types.push(quote! { #ty });
+                }
+            }
+
+            repr_generics.push(generic);
+        }
+
+        let target = pattern(shape);
+        let construct = quote! {
+            #target {
+                #(#field_idents,)*
+                #(#redacted_field_idents: ::core::default::Default::default(),)*
+                #(#bindings,)*
+            }
+        };
+
+        match shape.variant_ident {
+            None => {
+                repr_members.push(quote! { #(#members,)* });
+                arms.push(quote! {
+                    __Repr { fields: #fields_ident { #(#field_idents,)* }, #(#repr_bindings,)* } => #construct,
+                });
+            }
+            Some(variant_ident) => {
+                repr_members.push(quote! { #variant_ident { #(#members,)* }, });
+                arms.push(quote! {
+                    __Repr::#variant_ident { fields: #fields_ident { #(#field_idents,)* }, #(#repr_bindings,)* } => #construct,
+                });
+            }
+        }
+    }
+
+    for repr_ty in std_source_bounds {
+        inferred_bounds.insert(repr_ty, quote! { #serde_path::Deserialize<'__de> });
+    }
+
+    // enums are tagged by the `variant` key, structs ignore it
+    let repr_def = match shapes.first().and_then(|shape| shape.variant_ident) {
+        None => quote! {
+            #[derive(#serde_path::Deserialize)]
+            #serde_crate_attr
+            struct __Repr < #(#repr_generics,)* > {
+                #(#repr_members)*
+            }
+        },
+        Some(_) => quote! {
+            #[derive(#serde_path::Deserialize)]
+            #serde_crate_attr
+            #[serde(tag = "variant")]
+            enum __Repr < #(#repr_generics,)* > {
+                #(#repr_members)*
+            }
+        },
+    };
+
+    let mut de_generics = generics.clone();
+    de_generics.params.insert(0, parse_quote! { '__de });
+    let (de_impl_generics, _, _) = de_generics.split_for_impl();
+    let (_, ty_generics, where_clause) = generics.split_for_impl();
+    let where_clause = inferred_bounds.augment_where_clause(where_clause.cloned());
+
+    quote! {
+        impl #de_impl_generics #serde_path::Deserialize<'__de> for #type_ident #ty_generics #where_clause {
+            fn deserialize<__D>(deserializer: __D) -> ::core::result::Result<Self, __D::Error>
+            where
+                __D: #serde_path::Deserializer<'__de>,
+            {
+                #(#fields_structs)*
+
+                #repr_def
+
+                let repr = <__Repr < #(#repr_types,)* > as #serde_path::Deserialize>::deserialize(deserializer)?;
+
+                ::core::result::Result::Ok(match repr {
+                    #(#arms)*
+                })
+            }
+        }
+    }
+}
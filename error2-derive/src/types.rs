@@ -1,17 +1,18 @@
 use proc_macro2::{Span, TokenStream};
 use quote::{ToTokens, quote};
-use syn::{Attribute, Field, Ident, Token, Visibility, punctuated::Punctuated};
+use syn::{Attribute, Field, Ident, LitInt, LitStr, Token, Visibility, punctuated::Punctuated};
 
 #[derive(Clone, Copy)]
 pub(crate) enum ErrorKind {
     Root,
     Std,
     Err2,
+    Multi,
 }
 
 impl ErrorKind {
-    pub(crate) fn is_root(&self) -> bool {
-        matches!(self, ErrorKind::Root)
+    pub(crate) fn has_source(&self) -> bool {
+        matches!(self, ErrorKind::Std | ErrorKind::Err2)
     }
 }
 
@@ -33,10 +34,33 @@ pub(crate) struct TypeAttr {
     pub(crate) display: TypeDisplayAttr,
     pub(crate) context_vis: Visibility,
     pub(crate) mod_vis: Option<Visibility>,
+    pub(crate) public: Option<LitStr>,
+    pub(crate) level: Option<Ident>,
+    pub(crate) max_size: Option<LitInt>,
+    pub(crate) lazy: bool,
+    pub(crate) serialize: bool,
+    pub(crate) deserialize: bool,
 }
 
 pub(crate) struct VariantAttr {
     pub(crate) display: Option<TokenStream>,
+    pub(crate) public: Option<LitStr>,
+    pub(crate) level: Option<Ident>,
+}
+
+pub(crate) struct FieldAttr {
+    pub(crate) redact: bool,
+    pub(crate) sources: bool,
+}
+
+/// The fields of a struct or variant, as seen by the generated `Serialize` and `Deserialize`.
+pub(crate) struct SerdeShape<'a> {
+    pub(crate) variant_ident: Option<&'a Ident>,
+    pub(crate) error_kind: ErrorKind,
+    pub(crate) fields: Vec<&'a Field>,
+    pub(crate) redacted_fields: Vec<&'a Field>,
+    pub(crate) source_field: Option<&'a Field>,
+    pub(crate) backtrace_field: Option<&'a Field>,
 }
 
 pub(crate) struct MyVariant {
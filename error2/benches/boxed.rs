@@ -0,0 +1,147 @@
+//! Time and allocations of returning `Result`s with a boxed error.
+//!
+//! `BoxedError2` is a thin pointer to a boxed trait object, so it allocates
+//! twice when an error is boxed, but `Result<(), BoxedError2>` is one word
+//! smaller. `Result`s of values up to a word have the same size either way,
+//! the value is stored next to the null niche of the pointer. `FatBoxedError2`
+//! is the fat pointer it replaced, allocated once. Run with
+//! `cargo bench --bench boxed` and compare the `ok` rows, where results are
+//! returned through several calls, with the `alloc` rows of `err`.
+
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    mem,
+};
+
+use divan::{AllocProfiler, black_box};
+use error2::{Location, prelude::*};
+
+#[global_allocator]
+static ALLOC: AllocProfiler = AllocProfiler::system();
+
+fn main() {
+    assert_eq!(mem::size_of::<Result<(), BoxedError2>>(), 8);
+    assert_eq!(mem::size_of::<Result<(), FatBoxedError2>>(), 16);
+    assert_eq!(mem::size_of::<Result<u64, BoxedError2>>(), 16);
+    assert_eq!(mem::size_of::<Result<u64, FatBoxedError2>>(), 16);
+
+    divan::main();
+}
+
+#[derive(Debug, Error2)]
+#[error2(display("invalid digit `{digit}`"))]
+struct DigitError {
+    digit: char,
+    backtrace: Backtrace,
+}
+
+/// A boxed error as a fat pointer, the layout of `BoxedError2` before it was thin.
+#[derive(Debug)]
+struct FatBoxedError2(Box<dyn Error2 + Send + Sync>);
+
+impl Display for FatBoxedError2 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl Error for FatBoxedError2 {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.0.source()
+    }
+}
+
+impl Error2 for FatBoxedError2 {
+    fn backtrace(&self) -> &Backtrace {
+        self.0.backtrace()
+    }
+
+    fn backtrace_mut(&mut self) -> &mut Backtrace {
+        self.0.backtrace_mut()
+    }
+}
+
+trait Boxed: Error2 + Sized {
+    fn boxed(error: DigitError) -> Self;
+}
+
+impl Boxed for BoxedError2 {
+    fn boxed(error: DigitError) -> Self {
+        BoxedError2::from_err2(error)
+    }
+}
+
+impl Boxed for FatBoxedError2 {
+    fn boxed(error: DigitError) -> Self {
+        let mut error = FatBoxedError2(Box::new(error));
+        // records the error like `BoxedError2::from_err2`
+        error2::push_error(&mut error, Location::caller());
+        error
+    }
+}
+
+#[inline(never)]
+fn digit<E: Boxed>(byte: u8) -> Result<u64, E> {
+    match byte {
+        b'0'..=b'9' => Ok(u64::from(byte - b'0')),
+        _ => Err(E::boxed(
+            DigitError2 {
+                digit: byte as char,
+            }
+            .build(),
+        )),
+    }
+}
+
+#[inline(never)]
+fn checked<E: Boxed>(byte: u8) -> Result<u64, E> {
+    let digit = digit(byte).attach()?;
+    Ok(digit)
+}
+
+#[inline(never)]
+fn sum<E: Boxed>(bytes: &[u8]) -> Result<u64, E> {
+    let mut sum = 0;
+
+    for &byte in bytes {
+        sum += checked::<E>(byte).attach()?;
+    }
+
+    Ok(sum)
+}
+
+#[inline(never)]
+fn validate<E: Boxed>(byte: u8) -> Result<(), E> {
+    digit::<E>(byte).attach()?;
+    Ok(())
+}
+
+#[inline(never)]
+fn validate_all<E: Boxed>(bytes: &[u8]) -> Result<(), E> {
+    for &byte in bytes {
+        validate::<E>(byte).attach()?;
+    }
+
+    Ok(())
+}
+
+/// Every result is `Ok(())`, the hot path of a validator.
+#[divan::bench(types = [BoxedError2, FatBoxedError2])]
+fn ok_unit<E: Boxed>() -> bool {
+    let bytes = black_box([b'7'; 1024]);
+    validate_all::<E>(&bytes).is_ok()
+}
+
+/// Every result is `Ok`, the hot path of a parser.
+#[divan::bench(types = [BoxedError2, FatBoxedError2])]
+fn ok<E: Boxed>() -> u64 {
+    let bytes = black_box([b'7'; 1024]);
+    sum::<E>(&bytes).unwrap_or(0)
+}
+
+/// The last result is an error, which is boxed, propagated and dropped.
+#[divan::bench(types = [BoxedError2, FatBoxedError2])]
+fn err<E: Boxed>() -> bool {
+    sum::<E>(black_box(b"12x")).is_err()
+}
@@ -0,0 +1,45 @@
+//! Time of capturing a `Location`, the file name of which is interned.
+//!
+//! File names are cached per thread by the address of the `&'static str`, so
+//! only the first capture in each thread hashes the path. Run with
+//! `cargo bench --bench location` and compare the rows with more threads.
+
+use std::panic;
+
+use divan::black_box;
+use error2::{Location, prelude::*};
+
+fn main() {
+    divan::main();
+}
+
+const THREADS: &[usize] = &[1, 4, 16];
+
+/// Capturing the caller with the standard library, as a baseline.
+#[divan::bench(threads = THREADS)]
+fn std_caller() -> &'static panic::Location<'static> {
+    black_box(panic::Location::caller())
+}
+
+#[divan::bench(threads = THREADS)]
+fn caller() -> Location {
+    black_box(Location::caller())
+}
+
+/// Locations from different files, as recorded when an error crosses modules.
+#[divan::bench(threads = THREADS)]
+fn new_many_files() -> [Location; 4] {
+    black_box([
+        Location::new("src/server/http.rs", 10, 5),
+        Location::new("src/server/router.rs", 20, 9),
+        Location::new("src/storage/pool.rs", 30, 13),
+        Location::new("src/storage/query.rs", 40, 17),
+    ])
+}
+
+/// Propagating an error with `.attach()` records a location each time.
+#[divan::bench(threads = THREADS)]
+fn attach() -> bool {
+    let result: Result<(), BoxedError2> = ViaRoot("timeout").fail();
+    black_box(result.attach().attach().attach()).is_err()
+}
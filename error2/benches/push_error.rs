@@ -0,0 +1,113 @@
+//! Allocations and time of creating and propagating errors.
+//!
+//! The same chain of errors is benchmarked with display messages rendered when
+//! the errors are created, `eager`, and with `#[error2(lazy)]`, `lazy`. Run with
+//! `cargo bench --bench push_error` and compare the `alloc` rows of `discard`,
+//! the errors that are recovered from never format their messages when lazy.
+
+use std::num::ParseIntError;
+
+use divan::{AllocProfiler, black_box};
+use error2::prelude::*;
+
+#[global_allocator]
+static ALLOC: AllocProfiler = AllocProfiler::system();
+
+fn main() {
+    divan::main();
+}
+
+mod eager {
+    use super::*;
+
+    #[derive(Debug, Error2)]
+    #[error2(display("invalid number `{input}`"))]
+    pub(crate) struct ParseError {
+        input: String,
+        source: ParseIntError,
+        backtrace: Backtrace,
+    }
+
+    #[derive(Debug, Error2)]
+    #[error2(display("invalid field `{field}`"))]
+    pub(crate) struct FieldError {
+        field: &'static str,
+        source: ParseError,
+    }
+
+    fn parse(input: &str) -> Result<u64, FieldError> {
+        input
+            .parse::<u64>()
+            .context(ParseError2 { input })
+            .context(FieldError2 { field: "port" })
+    }
+
+    /// The error is created, propagated and dropped without being rendered.
+    #[divan::bench]
+    fn discard() -> bool {
+        parse(black_box("80x")).is_err()
+    }
+
+    /// The error is created and its backtrace is rendered.
+    #[divan::bench]
+    fn render() -> usize {
+        let err = parse(black_box("80x")).unwrap_err();
+        err.backtrace().error_message().len()
+    }
+}
+
+mod lazy {
+    use super::*;
+
+    #[derive(Debug, Error2)]
+    #[error2(display("invalid number `{input}`"), lazy)]
+    pub(crate) struct ParseError {
+        input: String,
+        source: ParseIntError,
+        backtrace: Backtrace,
+    }
+
+    #[derive(Debug, Error2)]
+    #[error2(display("invalid field `{field}`"), lazy)]
+    pub(crate) struct FieldError {
+        field: &'static str,
+        source: ParseError,
+    }
+
+    fn parse(input: &str) -> Result<u64, FieldError> {
+        input
+            .parse::<u64>()
+            .context(ParseError2 { input })
+            .context(FieldError2 { field: "port" })
+    }
+
+    /// The error is created, propagated and dropped without being rendered.
+    #[divan::bench]
+    fn discard() -> bool {
+        parse(black_box("80x")).is_err()
+    }
+
+    /// The error is created and its display messages are resolved.
+    #[divan::bench]
+    fn resolve() -> bool {
+        let err = parse(black_box("80x")).unwrap_err();
+        err.backtrace().public_message().is_none()
+    }
+
+    /// The error is created and its backtrace is rendered.
+    #[divan::bench]
+    fn render() -> usize {
+        let err = parse(black_box("80x")).unwrap_err();
+        err.backtrace().error_message().len()
+    }
+}
+
+fn parse_boxed(input: &str) -> Result<u64, BoxedError2> {
+    input.parse::<u64>().context(ViaStd)
+}
+
+/// `BoxedError2` renders its display message eagerly.
+#[divan::bench]
+fn discard_boxed() -> bool {
+    parse_boxed(black_box("80x")).is_err()
+}
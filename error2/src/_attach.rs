@@ -53,6 +53,7 @@ use crate::{Error2, Location};
 ///
 /// if let Err(e) = outer() {
 ///     let msg = e.backtrace().error_message();
+/// #   let msg = error2::strip_stamps(&msg);
 ///
 ///     // Full error format with multiple locations:
 ///     // IoError: io error
@@ -77,7 +78,7 @@ pub trait Attach<Wrapper>: Sized {
     #[track_caller]
     #[inline]
     fn attach(self) -> Wrapper {
-        self.attach_location(Location::caller())
+        self.attach_location(Location::tracked())
     }
 
     /// Records an explicit location (rarely needed).
@@ -87,7 +88,11 @@ pub trait Attach<Wrapper>: Sized {
 impl<E: Error2> Attach<Self> for E {
     #[inline]
     fn attach_location(mut self, location: Location) -> Self {
-        self.backtrace_mut().push_location(location);
+        crate::push_location(&mut self, location);
+
+        #[cfg(feature = "tracing")]
+        crate::trace::attached(&self, location);
+
         self
     }
 }
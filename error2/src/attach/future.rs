@@ -1,4 +1,4 @@
-use std::{
+use core::{
     marker::PhantomData,
     pin::Pin,
     task::{Context, Poll},
@@ -0,0 +1,91 @@
+use core::fmt::Display;
+
+use crate::Error2;
+
+/// Attach typed values to an error in flight.
+///
+/// Values are stored in the error's [`Backtrace`](crate::Backtrace) and can be read
+/// back by later layers with [`Backtrace::request_ref`](crate::Backtrace::request_ref),
+/// without knowing the concrete error type.
+///
+/// # Example
+///
+/// ```
+/// use std::fmt;
+///
+/// use error2::prelude::*;
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// struct RequestId(u64);
+///
+/// impl fmt::Display for RequestId {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         write!(f, "request id: {}", self.0)
+///     }
+/// }
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// struct StatusHint(u16);
+///
+/// #[derive(Debug, Error2)]
+/// #[error2(display("user not found"))]
+/// struct UserNotFound {
+///     backtrace: Backtrace,
+/// }
+///
+/// fn load_user(request_id: RequestId) -> Result<(), UserNotFound> {
+///     UserNotFound2
+///         .fail()
+///         .attach_printable(request_id)
+///         .attach_value(StatusHint(404))
+/// }
+///
+/// fn handler() -> Result<(), BoxedError2> {
+///     load_user(RequestId(42)).context(ViaErr2)
+/// }
+///
+/// let err = handler().unwrap_err();
+///
+/// assert_eq!(err.backtrace().request_ref::<RequestId>(), Some(&RequestId(42)));
+/// assert_eq!(err.backtrace().request_ref::<StatusHint>(), Some(&StatusHint(404)));
+/// assert!(err.backtrace().error_message().contains("note: request id: 42"));
+/// ```
+pub trait AttachValue: Sized {
+    /// Attaches a typed value that is hidden from the rendered error message.
+    fn attach_value<V: Send + Sync + 'static>(self, value: V) -> Self;
+
+    /// Attaches a typed value that is shown in the rendered error message.
+    fn attach_printable<V: Display + Send + Sync + 'static>(self, value: V) -> Self;
+}
+
+impl<E: Error2> AttachValue for E {
+    #[inline]
+    fn attach_value<V: Send + Sync + 'static>(mut self, value: V) -> Self {
+        self.backtrace_mut().attach_value(value);
+        self
+    }
+
+    #[inline]
+    fn attach_printable<V: Display + Send + Sync + 'static>(mut self, value: V) -> Self {
+        self.backtrace_mut().attach_printable(value);
+        self
+    }
+}
+
+impl<T, E: Error2> AttachValue for Result<T, E> {
+    #[inline]
+    fn attach_value<V: Send + Sync + 'static>(self, value: V) -> Self {
+        match self {
+            Ok(t) => Ok(t),
+            Err(e) => Err(e.attach_value(value)),
+        }
+    }
+
+    #[inline]
+    fn attach_printable<V: Display + Send + Sync + 'static>(self, value: V) -> Self {
+        match self {
+            Ok(t) => Ok(t),
+            Err(e) => Err(e.attach_printable(value)),
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use alloc::{boxed::Box, string::ToString, sync::Arc};
+use core::{
+    any::{Any, TypeId},
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+#[derive(Clone)]
+pub(crate) struct Attachment {
+    value: Option<Arc<dyn Any + Send + Sync>>,
+    display: Option<Box<str>>,
+}
+
+impl Attachment {
+    pub(super) fn hidden<T: Send + Sync + 'static>(value: T) -> Self {
+        Self {
+            value: Some(Arc::new(value)),
+            display: None,
+        }
+    }
+
+    pub(super) fn printable<T: fmt::Display + Send + Sync + 'static>(value: T) -> Self {
+        let display = value.to_string().into();
+
+        Self {
+            value: Some(Arc::new(value)),
+            display: Some(display),
+        }
+    }
+
+    /// An attachment deserialized from its rendered text, without a typed value.
+    pub(super) fn note(display: Box<str>) -> Self {
+        Self {
+            value: None,
+            display: Some(display),
+        }
+    }
+
+    pub(crate) fn downcast_ref<T: 'static>(&self) -> Option<&T> {
+        self.value.as_deref()?.downcast_ref::<T>()
+    }
+
+    pub(crate) fn display(&self) -> Option<&str> {
+        self.display.as_deref()
+    }
+
+    /// The type of a hidden value, which has no rendered text to compare.
+    fn hidden_type(&self) -> Option<TypeId> {
+        match (&self.value, &self.display) {
+            (Some(value), None) => Some(Any::type_id(&**value)),
+            _ => None,
+        }
+    }
+
+    fn key(&self) -> (Option<&str>, Option<TypeId>) {
+        (self.display(), self.hidden_type())
+    }
+}
+
+impl fmt::Debug for Attachment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Attachment")
+            .field("display", &self.display)
+            .finish_non_exhaustive()
+    }
+}
+
+// Typed values are opaque, so attachments are compared by their rendered text,
+// and hidden values, which have none, by their type.
+
+impl PartialEq for Attachment {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Attachment {}
+
+impl PartialOrd for Attachment {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Attachment {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl Hash for Attachment {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
@@ -0,0 +1,163 @@
+use alloc::boxed::Box;
+use core::{
+    borrow::{Borrow, BorrowMut},
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::{Deref, DerefMut},
+};
+
+use super::Backtrace;
+
+static EMPTY: Backtrace = Backtrace::new();
+
+/// A [`Backtrace`] that is one pointer wide and only allocates once an entry is recorded.
+///
+/// Use it, or `Box<Backtrace>`, as the type of the `backtrace` field to keep
+/// error types, and every `Result` carrying them, small.
+///
+/// Building an error records its message and location, so with the default
+/// [capture level](crate::capture::CaptureLevel) the allocation happens when
+/// the error is built. At the `Off` level, building and propagating the error
+/// records nothing and does not allocate.
+///
+/// ```
+/// use std::mem::size_of;
+///
+/// use error2::{BoxedBacktrace, prelude::*};
+///
+/// #[derive(Debug, Error2)]
+/// #[error2(display("invalid id: {id}"))]
+/// struct InvalidId {
+///     id: u32,
+///     backtrace: BoxedBacktrace,
+/// }
+///
+/// assert_eq!(size_of::<InvalidId>(), 16);
+///
+/// let err = InvalidId2 { id: 7u32 }.build();
+/// assert!(err.backtrace().error_message().contains("InvalidId: invalid id: 7"));
+/// ```
+#[derive(Clone, Default)]
+pub struct BoxedBacktrace(Option<Box<Backtrace>>);
+
+impl BoxedBacktrace {
+    /// Creates an empty backtrace without allocating.
+    #[inline]
+    pub const fn new() -> Self {
+        Self(None)
+    }
+}
+
+impl From<Backtrace> for BoxedBacktrace {
+    #[inline]
+    fn from(backtrace: Backtrace) -> Self {
+        if backtrace.is_empty() {
+            Self(None)
+        } else {
+            Self(Some(Box::new(backtrace)))
+        }
+    }
+}
+
+impl From<BoxedBacktrace> for Backtrace {
+    #[inline]
+    fn from(backtrace: BoxedBacktrace) -> Self {
+        match backtrace.0 {
+            Some(backtrace) => *backtrace,
+            None => Backtrace::new(),
+        }
+    }
+}
+
+impl Deref for BoxedBacktrace {
+    type Target = Backtrace;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        match &self.0 {
+            Some(backtrace) => backtrace,
+            None => &EMPTY,
+        }
+    }
+}
+
+impl DerefMut for BoxedBacktrace {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.0.get_or_insert_with(|| Box::new(Backtrace::new()))
+    }
+}
+
+impl Borrow<Backtrace> for BoxedBacktrace {
+    #[inline]
+    fn borrow(&self) -> &Backtrace {
+        self
+    }
+}
+
+impl BorrowMut<Backtrace> for BoxedBacktrace {
+    #[inline]
+    fn borrow_mut(&mut self) -> &mut Backtrace {
+        self
+    }
+}
+
+impl fmt::Debug for BoxedBacktrace {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl PartialEq for BoxedBacktrace {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl Eq for BoxedBacktrace {}
+
+impl PartialOrd for BoxedBacktrace {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BoxedBacktrace {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
+impl Hash for BoxedBacktrace {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state);
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for BoxedBacktrace {
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        (**self).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for BoxedBacktrace {
+    #[inline]
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Backtrace::deserialize(deserializer).map(Self::from)
+    }
+}
@@ -0,0 +1,695 @@
+//! Compact binary encoding of [`Backtrace`], for high-volume logs and IPC.
+//!
+//! Unlike the serde format, the encoding does not repeat file names and type
+//! names: every string is stored once in a string table at the start of the
+//! message and referenced by its index. Lines, columns, counts and indexes are
+//! LEB128 varints.
+//!
+//! # Format
+//!
+//! ```text
+//! message   = "E2BT" version:u8 strings backtrace
+//! strings   = count:varint { len:varint utf8-bytes }
+//! backtrace = count:varint { entry }
+//! entry     = 0x00 type_name:str display:opt-str public:opt-str   // error
+//!           | 0x01 count:varint { file:str line:varint column:varint } // 1 or 2 locations
+//!           | 0x02 note:str
+//!           | 0x03 backtrace                                      // child
+//!           | 0x04 backtrace                                      // suppressed
+//!           | 0x05 count:varint                                   // repeated
+//!           | 0x06 count:varint                                   // elided
+//! str       = index:varint
+//! opt-str   = 0 | (index + 1):varint
+//! ```
+//!
+//! Locations, repeated and elided entries refer to the error before them, so a
+//! backtrace that records them before its first error is rejected.
+//!
+//! Typed attachments without a display message are skipped, like in the serde
+//! format. The creation times and threads recorded with the `timestamps` and
+//! `threads` features are not encoded, so decoded backtraces show neither.
+//!
+//! # Example
+//!
+//! ```
+//! use error2::{codec, prelude::*};
+//!
+//! #[derive(Debug, Error2)]
+//! #[error2(display("disk full"))]
+//! struct DiskFull {
+//!     backtrace: Backtrace,
+//! }
+//!
+//! let error = DiskFull2.fail::<()>().attach().unwrap_err();
+//!
+//! let bytes = codec::encode(error.backtrace());
+//! let decoded = codec::decode(&bytes).unwrap();
+//! assert_eq!(&decoded, error.backtrace());
+//!
+//! assert!(codec::decode(&bytes[..bytes.len() - 1]).is_err());
+//! ```
+
+use alloc::{boxed::Box, collections::BTreeMap, string::String, vec::Vec};
+use core::{error::Error, fmt, str};
+
+use super::{
+    Backtrace, BakctraceEntry,
+    attachment::Attachment,
+    double_locations::{DoubleLocations, RemoteLocation},
+    message::Message,
+    stamp::Stamp,
+};
+
+const MAGIC: &[u8; 4] = b"E2BT";
+
+/// The current version of the format, bumped on incompatible changes.
+const VERSION: u8 = 1;
+
+/// How deeply child and suppressed backtraces may be nested in decoded input.
+const MAX_DEPTH: usize = 64;
+
+const TAG_MESSAGE: u8 = 0;
+const TAG_LOCATIONS: u8 = 1;
+const TAG_NOTE: u8 = 2;
+const TAG_CHILD: u8 = 3;
+const TAG_SUPPRESSED: u8 = 4;
+const TAG_REPEATED: u8 = 5;
+const TAG_ELIDED: u8 = 6;
+
+/// Encodes a backtrace in the compact binary format.
+///
+/// See the [module documentation](self) for the format.
+pub fn encode(backtrace: &Backtrace) -> Vec<u8> {
+    let mut encoder = Encoder::default();
+    encoder.backtrace(backtrace);
+
+    let mut out = Vec::with_capacity(
+        MAGIC.len()
+            + 1
+            + encoder.strings.iter().map(|s| s.len() + 1).sum::<usize>()
+            + encoder.body.len(),
+    );
+    out.extend_from_slice(MAGIC);
+    out.push(VERSION);
+
+    write_varint(&mut out, encoder.strings.len() as u32);
+    for s in &encoder.strings {
+        write_varint(&mut out, s.len() as u32);
+        out.extend_from_slice(s.as_bytes());
+    }
+
+    out.extend_from_slice(&encoder.body);
+    out
+}
+
+/// Decodes a backtrace encoded by [`encode`].
+///
+/// The input is fully validated, malformed or truncated input is rejected
+/// with a [`DecodeError`] rather than producing a partial backtrace.
+pub fn decode(bytes: &[u8]) -> Result<Backtrace, DecodeError> {
+    let mut decoder = Decoder {
+        bytes,
+        pos: 0,
+        strings: Vec::new(),
+    };
+
+    decoder.header()?;
+    decoder.strings()?;
+    let backtrace = decoder.backtrace(0)?;
+
+    if decoder.pos != bytes.len() {
+        return Err(decoder.error(DecodeErrorKind::TrailingBytes));
+    }
+
+    Ok(backtrace)
+}
+
+/// The error returned by [`decode`] for malformed input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeError {
+    offset: usize,
+    kind: DecodeErrorKind,
+}
+
+impl DecodeError {
+    /// Returns the offset of the byte at which the input was rejected.
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid backtrace encoding at byte {}: {}",
+            self.offset, self.kind
+        )
+    }
+}
+
+impl Error for DecodeError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DecodeErrorKind {
+    BadMagic,
+    UnsupportedVersion(u8),
+    UnexpectedEnd,
+    BadVarint,
+    BadUtf8,
+    BadStringIndex(u32),
+    BadTag(u8),
+    BadLocationCount(u32),
+    LocationsBeforeError,
+    MarkerBeforeError,
+    TooDeep,
+    TrailingBytes,
+}
+
+impl fmt::Display for DecodeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadMagic => f.write_str("not a backtrace"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported version {}, expected {}", version, VERSION)
+            }
+            Self::UnexpectedEnd => f.write_str("unexpected end of input"),
+            Self::BadVarint => f.write_str("malformed varint"),
+            Self::BadUtf8 => f.write_str("string is not valid UTF-8"),
+            Self::BadStringIndex(index) => write!(f, "string index {} is out of range", index),
+            Self::BadTag(tag) => write!(f, "unknown entry tag {}", tag),
+            Self::BadLocationCount(count) => {
+                write!(f, "expected 1 or 2 locations, found {}", count)
+            }
+            Self::LocationsBeforeError => f.write_str("locations recorded before any error"),
+            Self::MarkerBeforeError => {
+                f.write_str("repeated or elided locations recorded before any error")
+            }
+            Self::TooDeep => write!(f, "backtraces nested more than {} levels deep", MAX_DEPTH),
+            Self::TrailingBytes => f.write_str("trailing bytes after the backtrace"),
+        }
+    }
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u32) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+#[derive(Default)]
+struct Encoder<'a> {
+    strings: Vec<&'a str>,
+    indexes: BTreeMap<&'a str, u32>,
+    body: Vec<u8>,
+}
+
+impl<'a> Encoder<'a> {
+    /// Returns the index of `s` in the string table, adding it if needed.
+    fn index(&mut self, s: &'a str) -> u32 {
+        *self.indexes.entry(s).or_insert_with(|| {
+            self.strings.push(s);
+            (self.strings.len() - 1) as u32
+        })
+    }
+
+    fn str(&mut self, s: &'a str) {
+        let index = self.index(s);
+        write_varint(&mut self.body, index);
+    }
+
+    fn opt_str(&mut self, s: Option<&'a str>) {
+        let value = match s {
+            None => 0,
+            Some(s) => self.index(s) + 1,
+        };
+        write_varint(&mut self.body, value);
+    }
+
+    fn backtrace(&mut self, backtrace: &'a Backtrace) {
+        let entries = backtrace
+            .entries
+            .iter()
+            .filter(|entry| match entry {
+                BakctraceEntry::Attachment(attachment) => attachment.display().is_some(),
+                _ => true,
+            })
+            .collect::<Vec<_>>();
+
+        write_varint(&mut self.body, entries.len() as u32);
+
+        for entry in entries {
+            match entry {
+                BakctraceEntry::Message(message) => {
+                    self.body.push(TAG_MESSAGE);
+                    self.str(message.type_name());
+                    self.opt_str(message.display());
+                    self.opt_str(message.public());
+                }
+                BakctraceEntry::Locations(locations) => {
+                    self.body.push(TAG_LOCATIONS);
+                    write_varint(&mut self.body, locations.len() as u32);
+
+                    for location in locations.iter() {
+                        let (file, line, column) = location.key();
+                        self.str(file);
+                        write_varint(&mut self.body, line);
+                        write_varint(&mut self.body, column);
+                    }
+                }
+                BakctraceEntry::Attachment(attachment) => {
+                    // typed attachments were filtered out above
+                    if let Some(display) = attachment.display() {
+                        self.body.push(TAG_NOTE);
+                        self.str(display);
+                    }
+                }
+                BakctraceEntry::Child(child) => {
+                    self.body.push(TAG_CHILD);
+                    self.backtrace(child);
+                }
+                BakctraceEntry::Suppressed(suppressed) => {
+                    self.body.push(TAG_SUPPRESSED);
+                    self.backtrace(suppressed);
+                }
+                BakctraceEntry::Repeated(count) => {
+                    self.body.push(TAG_REPEATED);
+                    write_varint(&mut self.body, *count);
+                }
+                BakctraceEntry::Elided(count) => {
+                    self.body.push(TAG_ELIDED);
+                    write_varint(&mut self.body, *count);
+                }
+            }
+        }
+    }
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    strings: Vec<&'a str>,
+}
+
+impl<'a> Decoder<'a> {
+    fn error(&self, kind: DecodeErrorKind) -> DecodeError {
+        DecodeError {
+            offset: self.pos,
+            kind,
+        }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        let bytes = self
+            .bytes
+            .get(self.pos..)
+            .and_then(|rest| rest.get(..len))
+            .ok_or_else(|| self.error(DecodeErrorKind::UnexpectedEnd))?;
+
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint(&mut self) -> Result<u32, DecodeError> {
+        let start = self.pos;
+        let mut value = 0u32;
+
+        for i in 0..5 {
+            let byte = self.byte()?;
+
+            // the fifth byte only holds the 4 highest bits, and a final zero
+            // byte means the encoding is not the shortest one
+            if (i == 4 && byte > 0x0f) || (i > 0 && byte == 0) {
+                self.pos = start;
+                return Err(self.error(DecodeErrorKind::BadVarint));
+            }
+
+            value |= u32::from(byte & 0x7f) << (7 * i);
+
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        self.pos = start;
+        Err(self.error(DecodeErrorKind::BadVarint))
+    }
+
+    /// Reads a count of items, each taking at least `min_size` bytes, so that
+    /// a corrupted count cannot trigger a huge allocation.
+    fn count(&mut self, min_size: usize) -> Result<usize, DecodeError> {
+        let count = self.varint()? as usize;
+
+        if count.saturating_mul(min_size) > self.bytes.len() - self.pos {
+            return Err(self.error(DecodeErrorKind::UnexpectedEnd));
+        }
+
+        Ok(count)
+    }
+
+    fn header(&mut self) -> Result<(), DecodeError> {
+        if self.take(MAGIC.len()).ok() != Some(MAGIC) {
+            self.pos = 0;
+            return Err(self.error(DecodeErrorKind::BadMagic));
+        }
+
+        match self.byte()? {
+            VERSION => Ok(()),
+            version => {
+                self.pos -= 1;
+                Err(self.error(DecodeErrorKind::UnsupportedVersion(version)))
+            }
+        }
+    }
+
+    fn strings(&mut self) -> Result<(), DecodeError> {
+        let count = self.count(1)?;
+        self.strings.reserve_exact(count);
+
+        for _ in 0..count {
+            let len = self.varint()? as usize;
+            let start = self.pos;
+            let bytes = self.take(len)?;
+            let s = str::from_utf8(bytes).map_err(|e| DecodeError {
+                offset: start + e.valid_up_to(),
+                kind: DecodeErrorKind::BadUtf8,
+            })?;
+
+            self.strings.push(s);
+        }
+
+        Ok(())
+    }
+
+    fn str(&mut self) -> Result<&'a str, DecodeError> {
+        let start = self.pos;
+        let index = self.varint()?;
+
+        self.strings
+            .get(index as usize)
+            .copied()
+            .ok_or(DecodeError {
+                offset: start,
+                kind: DecodeErrorKind::BadStringIndex(index),
+            })
+    }
+
+    fn opt_str(&mut self) -> Result<Option<&'a str>, DecodeError> {
+        let start = self.pos;
+
+        match self.varint()? {
+            0 => Ok(None),
+            index => self
+                .strings
+                .get(index as usize - 1)
+                .copied()
+                .map(Some)
+                .ok_or(DecodeError {
+                    offset: start,
+                    kind: DecodeErrorKind::BadStringIndex(index - 1),
+                }),
+        }
+    }
+
+    fn backtrace(&mut self, depth: usize) -> Result<Backtrace, DecodeError> {
+        if depth > MAX_DEPTH {
+            return Err(self.error(DecodeErrorKind::TooDeep));
+        }
+
+        // every entry takes at least two bytes
+        let count = self.count(2)?;
+        let mut entries = Vec::with_capacity(count);
+        let mut seen_error = false;
+
+        for _ in 0..count {
+            let tag_pos = self.pos;
+
+            let entry = match self.byte()? {
+                TAG_MESSAGE => {
+                    let type_name = self.str()?;
+                    let display = self.opt_str()?.map(String::from);
+                    let public = self.opt_str()?;
+
+                    seen_error = true;
+                    BakctraceEntry::Message(Message::deserialized(type_name, display, public))
+                }
+                TAG_LOCATIONS => {
+                    if !seen_error {
+                        self.pos = tag_pos;
+                        return Err(self.error(DecodeErrorKind::LocationsBeforeError));
+                    }
+
+                    let count_pos = self.pos;
+                    let count = self.varint()?;
+                    if !matches!(count, 1 | 2) {
+                        self.pos = count_pos;
+                        return Err(self.error(DecodeErrorKind::BadLocationCount(count)));
+                    }
+
+                    let mut locations = Vec::with_capacity(count as usize);
+                    for _ in 0..count {
+                        let file = self.str()?;
+                        let line = self.varint()?;
+                        let column = self.varint()?;
+
+                        locations.push(RemoteLocation::new(
+                            file.into(),
+                            line,
+                            column,
+                            Stamp::none(),
+                        ));
+                    }
+
+                    BakctraceEntry::Locations(DoubleLocations::remote(locations))
+                }
+                TAG_NOTE => {
+                    let note = self.str()?;
+                    BakctraceEntry::Attachment(Box::new(Attachment::note(note.into())))
+                }
+                TAG_CHILD => BakctraceEntry::Child(Box::new(self.backtrace(depth + 1)?)),
+                TAG_SUPPRESSED => BakctraceEntry::Suppressed(Box::new(self.backtrace(depth + 1)?)),
+                TAG_REPEATED | TAG_ELIDED if !seen_error => {
+                    self.pos = tag_pos;
+                    return Err(self.error(DecodeErrorKind::MarkerBeforeError));
+                }
+                TAG_REPEATED => BakctraceEntry::Repeated(self.varint()?),
+                TAG_ELIDED => BakctraceEntry::Elided(self.varint()?),
+                tag => {
+                    self.pos = tag_pos;
+                    return Err(self.error(DecodeErrorKind::BadTag(tag)));
+                }
+            };
+
+            entries.push(entry);
+        }
+
+        Ok(Backtrace {
+            entries: entries.into(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::{Attach, BoxedError2, Error2, Location, Suppress, extract::strip_stamps};
+
+    fn sample() -> Backtrace {
+        let mut error = BoxedError2::from_root("timeout")
+            .attach_location(Location::new("src/peer.rs", 1, 1))
+            .attach_location(Location::new("src/peer.rs", 1, 1))
+            .attach_location(Location::new("src/peer.rs", 300, 17));
+        error.backtrace_mut().attach_printable("peer: 10.0.0.1");
+
+        let mut child = BoxedError2::from_root("shard 3 failed").backtrace().clone();
+        child.push_location(Location::new("src/shard.rs", 7, 9));
+        error.backtrace_mut().push_child(child);
+
+        error
+            .suppress(BoxedError2::from_root("rollback failed"))
+            .attach_location(Location::new("src/main.rs", 3, 1))
+            .backtrace()
+            .clone()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let origin = sample();
+        let bytes = encode(&origin);
+        let decoded = decode(&bytes).unwrap();
+
+        assert_eq!(origin, decoded);
+        assert!(decoded.messages().all(|message| message.is_remote()));
+        assert_eq!(
+            strip_stamps(&origin.error_message()),
+            strip_stamps(&decoded.error_message())
+        );
+        assert_eq!(encode(&decoded), bytes);
+
+        assert_eq!(
+            decode(&encode(&Backtrace::new())).unwrap(),
+            Backtrace::new()
+        );
+    }
+
+    #[test]
+    fn test_strings_are_stored_once() {
+        let mut error = BoxedError2::from_root("timeout");
+        for line in 1..=20 {
+            error = error.attach_location(Location::new("src/some/long/path/to/retry.rs", line, 1));
+        }
+
+        let bytes = encode(error.backtrace());
+        let needle = b"src/some/long/path/to/retry.rs";
+        assert_eq!(
+            bytes.windows(needle.len()).filter(|w| w == needle).count(),
+            1
+        );
+        assert_eq!(decode(&bytes).unwrap(), *error.backtrace());
+    }
+
+    #[test]
+    fn test_hidden_attachments_are_skipped() {
+        let mut error = BoxedError2::from_root("timeout");
+        error.backtrace_mut().attach_value(42_u32);
+
+        let decoded = decode(&encode(error.backtrace())).unwrap();
+        assert_eq!(decoded.request_ref::<u32>(), None);
+        assert_eq!(
+            strip_stamps(&decoded.error_message()),
+            strip_stamps(&error.backtrace().error_message())
+        );
+    }
+
+    #[test]
+    fn test_rejects_malformed_input() {
+        let kind = |bytes: &[u8]| decode(bytes).unwrap_err().kind;
+
+        assert_eq!(kind(b""), DecodeErrorKind::BadMagic);
+        assert_eq!(kind(b"E2BX\x01\x00\x00"), DecodeErrorKind::BadMagic);
+        assert_eq!(
+            kind(b"E2BT\x02\x00\x00"),
+            DecodeErrorKind::UnsupportedVersion(2)
+        );
+        assert_eq!(kind(b"E2BT\x01\x00"), DecodeErrorKind::UnexpectedEnd);
+        assert_eq!(
+            kind(b"E2BT\x01\x00\x00\x00"),
+            DecodeErrorKind::TrailingBytes
+        );
+        assert_eq!(kind(b"E2BT\x01\x01\x01\xff\x00"), DecodeErrorKind::BadUtf8);
+        assert_eq!(
+            kind(b"E2BT\x01\x00\x01\x07\x00"),
+            DecodeErrorKind::BadTag(7)
+        );
+        assert_eq!(
+            kind(b"E2BT\x01\x00\x01\x00\x00"),
+            DecodeErrorKind::BadStringIndex(0)
+        );
+        assert_eq!(
+            kind(b"E2BT\x01\x01\x01E\x01\x01\x00\x01\x00"),
+            DecodeErrorKind::LocationsBeforeError
+        );
+        assert_eq!(
+            kind(b"E2BT\x01\x00\x01\x05\x01"),
+            DecodeErrorKind::MarkerBeforeError
+        );
+        assert_eq!(
+            kind(b"E2BT\x01\x00\x01\x06\x01"),
+            DecodeErrorKind::MarkerBeforeError
+        );
+        assert_eq!(
+            kind(b"E2BT\x01\x01\x01E\x02\x00\x00\x00\x00\x01\x03"),
+            DecodeErrorKind::BadLocationCount(3)
+        );
+        // overlong and overflowing varints
+        assert_eq!(kind(b"E2BT\x01\x80\x00\x00"), DecodeErrorKind::BadVarint);
+        assert_eq!(
+            kind(b"E2BT\x01\x01\x01E\x02\x00\x00\x00\x00\x05\xff\xff\xff\xff\x1f"),
+            DecodeErrorKind::BadVarint
+        );
+        // a huge count is rejected before allocating
+        assert_eq!(
+            kind(b"E2BT\x01\xff\xff\xff\xff\x0f"),
+            DecodeErrorKind::UnexpectedEnd
+        );
+
+        let error = decode(b"E2BT\x01\x00\x01\x07\x00").unwrap_err();
+        assert_eq!(error.offset(), 7);
+        assert_eq!(
+            error.to_string(),
+            "invalid backtrace encoding at byte 7: unknown entry tag 7"
+        );
+    }
+
+    #[test]
+    fn test_rejects_deep_nesting() {
+        let mut bytes = b"E2BT\x01\x00".to_vec();
+        for _ in 0..=MAX_DEPTH {
+            bytes.extend_from_slice(&[1, TAG_CHILD]);
+        }
+        bytes.push(0);
+
+        assert_eq!(decode(&bytes).unwrap_err().kind, DecodeErrorKind::TooDeep);
+    }
+
+    #[test]
+    fn test_truncated_input_is_rejected() {
+        let bytes = encode(&sample());
+
+        for len in 0..bytes.len() {
+            assert!(
+                decode(&bytes[..len]).is_err(),
+                "accepted a prefix of {} bytes",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn test_corrupted_input_does_not_panic() {
+        let bytes = encode(&sample());
+
+        // every single bit flip
+        for i in 0..bytes.len() {
+            for bit in 0..8 {
+                let mut corrupted = bytes.clone();
+                corrupted[i] ^= 1 << bit;
+
+                if let Ok(decoded) = decode(&corrupted) {
+                    let _ = decoded.error_message();
+                }
+            }
+        }
+
+        // random overwrites, from a fixed seed
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        for _ in 0..10_000 {
+            let mut corrupted = bytes.clone();
+            for _ in 0..=next() % 4 {
+                let i = next() as usize % corrupted.len();
+                corrupted[i] = next() as u8;
+            }
+            corrupted.truncate(corrupted.len() - next() as usize % 3);
+
+            if let Ok(decoded) = decode(&corrupted) {
+                let _ = decoded.error_message();
+            }
+        }
+    }
+}
@@ -1,35 +1,249 @@
+use alloc::boxed::Box;
+use core::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+use super::stamp::Stamp;
 use crate::Location;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub(crate) struct DoubleLocations([Location; 2]);
+/// A location deserialized from a remote backtrace.
+///
+/// The file name is owned, so deserializing backtraces from untrusted peers
+/// does not grow the global interner.
+#[derive(Debug, Clone)]
+pub(crate) struct RemoteLocation {
+    file: Box<str>,
+    line: u32,
+    column: u32,
+    stamp: Stamp,
+}
+
+impl RemoteLocation {
+    pub(super) fn new(file: Box<str>, line: u32, column: u32, stamp: Stamp) -> Self {
+        Self {
+            file,
+            line,
+            column,
+            stamp,
+        }
+    }
+
+    /// Parses the `file:line:column` form of a location.
+    #[cfg(feature = "serde")]
+    pub(super) fn parse(s: &str, stamp: Stamp) -> Option<Self> {
+        let (file, line, column) = crate::location::split(s)?;
+
+        Some(Self::new(file.into(), line, column, stamp))
+    }
+}
+
+/// A recorded location, either captured in this process or deserialized.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum LocationRef<'a> {
+    Local(Location, Stamp),
+    Remote(&'a RemoteLocation),
+}
+
+impl<'a> LocationRef<'a> {
+    pub(crate) fn key(&self) -> (&'a str, u32, u32) {
+        match self {
+            LocationRef::Local(location, _) => {
+                (location.file(), location.line(), location.column())
+            }
+            LocationRef::Remote(location) => (&location.file, location.line, location.column),
+        }
+    }
+
+    /// Returns when the location was recorded, stamps are ignored by comparisons.
+    #[cfg_attr(not(feature = "timestamps"), allow(dead_code))]
+    pub(crate) fn stamp(&self) -> Stamp {
+        match self {
+            LocationRef::Local(_, stamp) => *stamp,
+            LocationRef::Remote(location) => location.stamp,
+        }
+    }
+}
+
+impl PartialEq for LocationRef<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (LocationRef::Local(a, _), LocationRef::Local(b, _)) => a == b,
+            _ => self.key() == other.key(),
+        }
+    }
+}
+
+impl Eq for LocationRef<'_> {}
+
+impl PartialOrd for LocationRef<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for LocationRef<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl Hash for LocationRef<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+impl fmt::Display for LocationRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (file, line, column) = self.key();
+        write!(f, "{}:{}:{}", file, line, column)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum DoubleLocations {
+    Local([Location; 2], [Stamp; 2]),
+    /// One or two deserialized locations, further locations are recorded in a new entry.
+    Remote(Box<[RemoteLocation]>),
+}
 
 impl DoubleLocations {
-    pub(super) const fn new(location: Location) -> Self {
+    /// Creates an entry of a location recorded now.
+    pub(super) fn new(location: Location) -> Self {
+        Self::with_stamp(location, Stamp::now())
+    }
+
+    fn with_stamp(location: Location, stamp: Stamp) -> Self {
         debug_assert!(!location.is_uninit());
-        Self([location, Location::uninit()])
+        Self::Local([location, Location::uninit()], [stamp, Stamp::none()])
+    }
+
+    /// Creates an entry of one or two deserialized locations.
+    pub(super) fn remote(locations: alloc::vec::Vec<RemoteLocation>) -> Self {
+        debug_assert!(matches!(locations.len(), 1 | 2));
+        Self::Remote(locations.into_boxed_slice())
+    }
+
+    pub(super) fn from_ref(location: LocationRef<'_>) -> Self {
+        match location {
+            LocationRef::Local(location, stamp) => Self::with_stamp(location, stamp),
+            LocationRef::Remote(location) => Self::Remote(Box::new([location.clone()])),
+        }
     }
 
     pub(super) const fn is_full(&self) -> bool {
-        let [first, second] = &self.0;
-        debug_assert!(!first.is_uninit());
+        match self {
+            Self::Local([first, second], _) => {
+                debug_assert!(!first.is_uninit());
 
-        !second.is_uninit()
+                !second.is_uninit()
+            }
+            Self::Remote(_) => true,
+        }
     }
 
-    pub(super) const fn push(&mut self, location: Location) -> Option<Location> {
-        let [first, second] = &mut self.0;
-        debug_assert!(!first.is_uninit());
+    pub(super) fn push(&mut self, location: Location) -> Option<Location> {
+        match self {
+            Self::Local([first, second], [_, stamp]) => {
+                debug_assert!(!first.is_uninit());
 
-        if second.is_uninit() {
-            *second = location;
-            None
-        } else {
-            Some(location)
+                if second.is_uninit() {
+                    *second = location;
+                    *stamp = Stamp::now();
+                    None
+                } else {
+                    Some(location)
+                }
+            }
+            Self::Remote(_) => Some(location),
         }
     }
 
-    pub(crate) const fn inner(&self) -> &[Location; 2] {
-        &self.0
+    pub(super) const fn len(&self) -> usize {
+        match self {
+            Self::Local([_, second], _) => {
+                if second.is_uninit() {
+                    1
+                } else {
+                    2
+                }
+            }
+            Self::Remote(locations) => locations.len(),
+        }
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = LocationRef<'_>> {
+        let (local, stamps, remote): (&[Location], &[Stamp], &[RemoteLocation]) = match self {
+            Self::Local(locations, stamps) => (&locations[..self.len()], stamps, &[]),
+            Self::Remote(locations) => (&[], &[], locations),
+        };
+
+        local
+            .iter()
+            .zip(stamps)
+            .map(|(location, stamp)| LocationRef::Local(*location, *stamp))
+            .chain(remote.iter().map(LocationRef::Remote))
+    }
+
+    pub(super) fn first(&self) -> LocationRef<'_> {
+        self.iter().next().expect("there is at least one location")
+    }
+
+    pub(super) fn last(&self) -> LocationRef<'_> {
+        self.iter().last().expect("there is at least one location")
+    }
+
+    /// Removes the second location, returns `false` if there is only one.
+    pub(super) fn pop(&mut self) -> bool {
+        match self {
+            Self::Local([first, second], [_, stamp]) => {
+                debug_assert!(!first.is_uninit());
+
+                if second.is_uninit() {
+                    false
+                } else {
+                    *second = Location::uninit();
+                    *stamp = Stamp::none();
+                    true
+                }
+            }
+            Self::Remote(locations) => match &**locations {
+                [first, _second] => {
+                    *locations = Box::new([first.clone()]);
+                    true
+                }
+                _ => false,
+            },
+        }
+    }
+}
+
+impl PartialEq for DoubleLocations {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for DoubleLocations {}
+
+impl PartialOrd for DoubleLocations {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DoubleLocations {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl Hash for DoubleLocations {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for location in self.iter() {
+            location.hash(state);
+        }
     }
 }
@@ -0,0 +1,252 @@
+use alloc::{sync::Arc, vec::Vec};
+use core::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+    iter, mem,
+    sync::atomic::{AtomicBool, Ordering as AtomicOrdering},
+};
+
+use super::BakctraceEntry;
+
+/// The entries of a backtrace, stored as a persistent list of chunks.
+///
+/// Clones share all their chunks. The first push to a shared list starts a new
+/// chunk on top of the shared ones, so clones only diverge from that point on.
+#[derive(Clone, Default)]
+pub(crate) struct Entries {
+    last: Option<Arc<Chunk>>,
+}
+
+#[derive(Default)]
+struct Chunk {
+    prev: Option<Link>,
+    entries: Vec<BakctraceEntry>,
+    /// Set if a message of the list ending with this chunk may still have a
+    /// pending display message, so that resolving is skipped in O(1) otherwise.
+    pending: AtomicBool,
+}
+
+/// The first `len` entries of a chunk, entries after them belong to another list.
+#[derive(Clone)]
+struct Link {
+    chunk: Arc<Chunk>,
+    len: usize,
+}
+
+impl Drop for Chunk {
+    fn drop(&mut self) {
+        // unlink the chain iteratively, a long chain would overflow the stack otherwise
+        let mut prev = self.prev.take();
+
+        while let Some(link) = prev {
+            prev = match Arc::into_inner(link.chunk) {
+                Some(mut chunk) => chunk.prev.take(),
+                None => None,
+            };
+        }
+    }
+}
+
+impl Entries {
+    #[inline]
+    pub(crate) const fn new() -> Self {
+        Self { last: None }
+    }
+
+    /// Returns the entries of each chunk, the most recent chunk first.
+    fn slices(&self) -> impl Iterator<Item = &[BakctraceEntry]> {
+        let mut next = self
+            .last
+            .as_deref()
+            .map(|chunk| (chunk, chunk.entries.len()));
+
+        iter::from_fn(move || {
+            let (chunk, len) = next?;
+            next = chunk.prev.as_ref().map(|link| (&*link.chunk, link.len));
+            Some(&chunk.entries[..len])
+        })
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &BakctraceEntry> {
+        let mut slices = self.slices();
+        let last = slices.next().unwrap_or_default();
+        // empty for a list with a single chunk, so no allocation is needed
+        let earlier = slices.collect::<Vec<_>>();
+
+        earlier.into_iter().rev().flatten().chain(last)
+    }
+
+    pub(crate) fn iter_rev(&self) -> impl Iterator<Item = &BakctraceEntry> {
+        self.slices().flat_map(|entries| entries.iter().rev())
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.slices().map(<[_]>::len).sum()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.slices().all(<[_]>::is_empty)
+    }
+
+    pub(crate) fn first(&self) -> Option<&BakctraceEntry> {
+        self.slices().filter_map(<[_]>::first).last()
+    }
+
+    pub(crate) fn last(&self) -> Option<&BakctraceEntry> {
+        self.iter_rev().next()
+    }
+
+    /// Returns `true` if both lists end with the same chunk, so none of their
+    /// entries are stored twice.
+    #[cfg(test)]
+    pub(crate) fn shares_entries_with(&self, other: &Self) -> bool {
+        match (&self.last, &other.last) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    pub(crate) fn push(&mut self, entry: BakctraceEntry) {
+        let pending = matches!(&entry, BakctraceEntry::Message(message) if message.is_pending());
+
+        self.tail_mut(0).push(entry);
+
+        if pending {
+            self.set_pending(true);
+        }
+    }
+
+    /// Returns `true` if a message may still have a pending display message.
+    pub(crate) fn has_pending(&self) -> bool {
+        self.last
+            .as_ref()
+            .is_some_and(|chunk| chunk.pending.load(AtomicOrdering::Relaxed))
+    }
+
+    pub(crate) fn set_pending(&self, pending: bool) {
+        if let Some(chunk) = &self.last {
+            chunk.pending.store(pending, AtomicOrdering::Relaxed);
+        }
+    }
+
+    /// Returns the entries of the last chunk, which is owned by this list and
+    /// holds at least `min` entries, or all entries if there are fewer.
+    ///
+    /// Entries of shared chunks are copied, at most `min` of them.
+    pub(crate) fn tail_mut(&mut self, min: usize) -> &mut Vec<BakctraceEntry> {
+        let last = self.last.get_or_insert_with(Default::default);
+
+        if Arc::get_mut(last).is_none() {
+            let len = last.entries.len();
+            let shared = mem::take(last);
+            let chunk = Arc::get_mut(last).expect("a new chunk is not shared");
+
+            chunk.pending = AtomicBool::new(shared.pending.load(AtomicOrdering::Relaxed));
+            chunk.prev = Some(Link { chunk: shared, len });
+        }
+
+        let chunk = Arc::get_mut(last).expect("the last chunk is not shared");
+
+        while chunk.entries.len() < min {
+            let Some(link) = chunk.prev.take() else {
+                break;
+            };
+
+            let taken = (min - chunk.entries.len()).min(link.len);
+            let start = link.len - taken;
+
+            let mut entries = link.chunk.entries[start..link.len].to_vec();
+            entries.append(&mut chunk.entries);
+            chunk.entries = entries;
+
+            chunk.prev = if start == 0 {
+                link.chunk.prev.clone()
+            } else {
+                Some(Link {
+                    chunk: link.chunk,
+                    len: start,
+                })
+            };
+        }
+
+        &mut chunk.entries
+    }
+
+    /// Moves the entries out, entries of shared chunks are copied.
+    pub(crate) fn into_vec(mut self) -> Vec<BakctraceEntry> {
+        let mut next = self.last.take().map(|chunk| {
+            let len = chunk.entries.len();
+            Link { chunk, len }
+        });
+        let mut slices = Vec::new();
+
+        while let Some(link) = next {
+            match Arc::try_unwrap(link.chunk) {
+                Ok(mut chunk) => {
+                    chunk.entries.truncate(link.len);
+                    slices.push(mem::take(&mut chunk.entries));
+                    next = chunk.prev.take();
+                }
+                Err(chunk) => {
+                    slices.push(chunk.entries[..link.len].to_vec());
+                    next = chunk.prev.clone();
+                }
+            }
+        }
+
+        slices.into_iter().rev().flatten().collect()
+    }
+}
+
+impl From<Vec<BakctraceEntry>> for Entries {
+    #[inline]
+    fn from(entries: Vec<BakctraceEntry>) -> Self {
+        if entries.is_empty() {
+            return Self::new();
+        }
+
+        let pending = entries
+            .iter()
+            .any(|entry| matches!(entry, BakctraceEntry::Message(message) if message.is_pending()));
+
+        Self {
+            last: Some(Arc::new(Chunk {
+                prev: None,
+                entries,
+                pending: AtomicBool::new(pending),
+            })),
+        }
+    }
+}
+
+impl PartialEq for Entries {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for Entries {}
+
+impl PartialOrd for Entries {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Entries {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl Hash for Entries {
+    // same as hashing a `Vec` of the entries
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+
+        for entry in self.iter() {
+            entry.hash(state);
+        }
+    }
+}
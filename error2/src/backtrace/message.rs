@@ -1,25 +1,244 @@
+#[cfg(feature = "std-backtrace")]
+use alloc::sync::Arc;
+use alloc::{boxed::Box, string::String, string::ToString};
+use core::{
+    cmp::Ordering,
+    fmt::{self, Display, Formatter},
+    hash::{Hash, Hasher},
+};
+
+#[cfg(feature = "timestamps")]
+use std::time::SystemTime;
+
+use once_cell::race::OnceBox;
+
 use crate::StrId;
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone)]
 pub(crate) struct Message {
     type_name: StrId,
-    display: Box<str>,
+    public: StrId,
+    text: Text,
+    /// The wall-clock time the error was created at, `None` if it is unknown.
+    #[cfg(feature = "timestamps")]
+    created: Option<SystemTime>,
+    /// The call stack captured at the origin of the chain, shared by clones.
+    #[cfg(feature = "std-backtrace")]
+    stack: Option<Arc<std::backtrace::Backtrace>>,
+    /// The spans active at the origin of the chain.
+    #[cfg(feature = "tracing")]
+    spans: Option<tracing_error::SpanTrace>,
+}
+
+/// Two variants, so that the pointer of `Rendered` is the niche of the enum
+/// and a `Message` is no larger than a `DoubleLocations`.
+#[derive(Clone)]
+enum Text {
+    /// Rendered when the error was created.
+    Rendered(Box<str>),
+    /// Set once the display message is resolved, or when the message is deserialized.
+    Deferred(OnceBox<Deferred>),
+}
+
+#[derive(Clone)]
+enum Deferred {
+    Rendered(Box<str>),
+    /// A message deserialized from a remote backtrace, its names are owned so
+    /// that deserializing backtraces from untrusted peers does not grow the
+    /// global interner. The interned names of the message are left uninit.
+    Remote(RemoteText),
+}
+
+#[derive(Clone)]
+struct RemoteText {
+    type_name: Box<str>,
+    display: Option<Box<str>>,
+    public: Option<Box<str>>,
 }
 
 impl Message {
-    pub(super) fn new(type_name: &'static str, display: String) -> Self {
+    /// Creates a message, the display message is pending if `display` is `None`.
+    pub(super) fn new(
+        type_name: &'static str,
+        display: Option<String>,
+        public: Option<&'static str>,
+    ) -> Self {
         Self {
             type_name: type_name.into(),
-            display: display.into(),
+            public: match public {
+                Some(public) => public.into(),
+                None => StrId::uninit(),
+            },
+            text: match display {
+                Some(display) => Text::Rendered(display.into_boxed_str()),
+                None => Text::Deferred(OnceBox::new()),
+            },
+            #[cfg(feature = "timestamps")]
+            created: Some(SystemTime::now()),
+            #[cfg(feature = "std-backtrace")]
+            stack: None,
+            #[cfg(feature = "tracing")]
+            spans: None,
+        }
+    }
+
+    /// Creates a message from deserialized parts.
+    pub(super) fn deserialized(
+        type_name: &str,
+        display: Option<String>,
+        public: Option<&str>,
+    ) -> Self {
+        Self {
+            type_name: StrId::uninit(),
+            public: StrId::uninit(),
+            text: Text::Deferred(OnceBox::with_value(Box::new(Deferred::Remote(
+                RemoteText {
+                    type_name: type_name.into(),
+                    display: display.map(String::into_boxed_str),
+                    public: public.map(Box::from),
+                },
+            )))),
+            #[cfg(feature = "timestamps")]
+            created: None,
+            #[cfg(feature = "std-backtrace")]
+            stack: None,
+            #[cfg(feature = "tracing")]
+            spans: None,
+        }
+    }
+
+    #[cfg(feature = "timestamps")]
+    pub(super) fn created(&self) -> Option<SystemTime> {
+        self.created
+    }
+
+    #[cfg(all(feature = "timestamps", feature = "serde"))]
+    pub(super) fn set_created(&mut self, created: SystemTime) {
+        self.created = Some(created);
+    }
+
+    #[cfg(feature = "std-backtrace")]
+    pub(crate) fn stack(&self) -> Option<&std::backtrace::Backtrace> {
+        self.stack.as_deref()
+    }
+
+    #[cfg(feature = "std-backtrace")]
+    pub(super) fn set_stack(&mut self, stack: std::backtrace::Backtrace) {
+        self.stack = Some(Arc::new(stack));
+    }
+
+    #[cfg(feature = "tracing")]
+    pub(crate) fn spans(&self) -> Option<&tracing_error::SpanTrace> {
+        self.spans.as_ref()
+    }
+
+    #[cfg(feature = "tracing")]
+    pub(super) fn set_spans(&mut self, spans: tracing_error::SpanTrace) {
+        self.spans = Some(spans);
+    }
+
+    fn deferred(&self) -> Option<&Deferred> {
+        match &self.text {
+            Text::Rendered(_) => None,
+            Text::Deferred(deferred) => deferred.get(),
         }
     }
 
-    pub(crate) const fn type_name(&self) -> &StrId {
-        &self.type_name
+    pub(crate) fn type_name(&self) -> &str {
+        match self.deferred() {
+            Some(Deferred::Remote(remote)) => &remote.type_name,
+            _ => self.type_name.into(),
+        }
+    }
+
+    /// Returns the display message, or `None` if it is still pending.
+    pub(crate) fn display(&self) -> Option<&str> {
+        match &self.text {
+            Text::Rendered(display) => Some(display),
+            Text::Deferred(deferred) => match deferred.get()? {
+                Deferred::Rendered(display) => Some(display),
+                Deferred::Remote(remote) => remote.display.as_deref(),
+            },
+        }
     }
 
-    pub(crate) const fn display(&self) -> &str {
-        &self.display
+    pub(super) fn is_pending(&self) -> bool {
+        match &self.text {
+            Text::Rendered(_) => false,
+            Text::Deferred(deferred) => deferred.get().is_none(),
+        }
+    }
+
+    pub(super) fn resolve(&self, display: &dyn Display) {
+        if let Text::Deferred(deferred) = &self.text {
+            deferred
+                .get_or_init(|| Box::new(Deferred::Rendered(display.to_string().into_boxed_str())));
+        }
+    }
+
+    pub(crate) fn public(&self) -> Option<&str> {
+        match self.deferred() {
+            Some(Deferred::Remote(remote)) => remote.public.as_deref(),
+            _ if self.public.is_uninit() => None,
+            _ => Some(self.public.into()),
+        }
+    }
+
+    /// Returns `true` if the message was deserialized and owns its names.
+    #[cfg(test)]
+    pub(super) fn is_remote(&self) -> bool {
+        matches!(self.deferred(), Some(Deferred::Remote(_)))
+    }
+
+    /// The public message follows from the type, so only the type name and the
+    /// resolved display message are compared.
+    fn key(&self) -> (&str, Option<&str>) {
+        (self.type_name(), self.display())
+    }
+}
+
+impl fmt::Debug for Message {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Message")
+            .field("type_name", &self.type_name())
+            .field("display", &self.display())
+            .field("public", &self.public())
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for Message {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Message {}
+
+impl PartialOrd for Message {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Message {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl Hash for Message {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+impl Display for Message {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.display() {
+            Some(display) => write!(f, "{}: {}", self.type_name(), display),
+            // the error was dropped before its display message was resolved
+            None => write!(f, "{}", self.type_name()),
+        }
     }
 }
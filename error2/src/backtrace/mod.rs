@@ -1,18 +1,50 @@
+mod attachment;
+mod boxed_backtrace;
+pub mod codec;
 mod double_locations;
+mod entries;
 mod message;
+#[cfg(feature = "serde")]
+mod schema;
+mod stamp;
 
-use std::{any, error::Error, fmt, mem};
+use alloc::{boxed::Box, string::String, string::ToString, vec, vec::Vec};
+use core::{any, error::Error, fmt, mem};
 
-use self::{double_locations::DoubleLocations, message::Message};
-use crate::Location;
+pub use self::boxed_backtrace::BoxedBacktrace;
+use self::{
+    attachment::Attachment,
+    double_locations::{DoubleLocations, LocationRef},
+    entries::Entries,
+    message::Message,
+    stamp::Stamp,
+};
+use crate::{
+    Location,
+    capture::{CaptureLevel, capture_level, max_locations},
+    render::Render,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) enum BakctraceEntry {
     Message(Message),
     Locations(DoubleLocations),
+    Attachment(Box<Attachment>),
+    Child(Box<Backtrace>),
+    Suppressed(Box<Backtrace>),
+    /// The preceding location was recorded this many more times in a row.
+    Repeated(u32),
+    /// This many locations were dropped to keep the backtrace bounded.
+    Elided(u32),
 }
 
+// timestamps, threads, call stacks and spans make the entries larger
+#[cfg(not(any(
+    feature = "timestamps",
+    feature = "threads",
+    feature = "std-backtrace",
+    feature = "tracing"
+)))]
 const _: () = {
     ["Size of `Message`"][mem::size_of::<Message>() - 24usize];
     ["Size of `DoubleLocations`"][mem::size_of::<DoubleLocations>() - 24usize];
@@ -66,6 +98,7 @@ const _: () = {
 ///
 /// if let Err(e) = operation() {
 ///     let msg = e.backtrace().error_message();
+/// #   let msg = error2::strip_stamps(&msg);
 ///
 ///     // Full error format with location tracking:
 ///     // TestError: test error
@@ -95,6 +128,53 @@ const _: () = {
 ///
 /// These methods use `#[track_caller]` to capture the caller's location without manual intervention.
 ///
+/// # Cloning
+///
+/// Clones share the recorded entries, so cloning an error to hand it to many
+/// waiters is cheap. Only the entries recorded after the clone are stored
+/// separately by each copy.
+///
+/// # Serialization
+///
+/// With the `serde` feature, a backtrace is serialized in a versioned schema
+/// that does not depend on how it is stored in memory:
+///
+/// ```json
+/// {
+///   "version": 1,
+///   "frames": [
+///     {
+///       "type_name": "std::io::error::Error",
+///       "display": "No such file or directory (os error 2)",
+///       "locations": []
+///     },
+///     {
+///       "type_name": "my_app::AppError",
+///       "display": "Config error",
+///       "public": "Something went wrong",
+///       "locations": ["src/main.rs:184:19", "src/main.rs:190:5"],
+///       "events": [
+///         { "at": 1, "event": { "repeated": 2 } },
+///         { "at": 2, "event": { "note": "request id: 42" } }
+///       ]
+///     }
+///   ]
+/// }
+/// ```
+///
+/// - `frames` lists the errors of the chain in the order they were recorded, the
+///   innermost first. `display` is `null` if the error was dropped before its
+///   message was rendered, and `public` is omitted unless the error has a public
+///   message.
+/// - `locations` are in the `file:line:column` form of [`Location`].
+/// - `events` are recorded after the first `at` locations of their frame, in order:
+///   `repeated` and `elided` counts, `note` for printable attachments, and `child`
+///   and `suppressed` for nested backtraces in the same schema. Events recorded
+///   before the first frame are listed at the top level.
+///
+/// Attached typed values and the threads recorded with the `threads` feature
+/// are not serialized. Deserializing fails for any `version` other than `1`.
+///
 /// # Example with Nested Errors
 ///
 /// ```
@@ -131,6 +211,7 @@ const _: () = {
 ///
 /// if let Err(e) = start_app() {
 ///     let msg = e.backtrace().error_message();
+/// #   let msg = error2::strip_stamps(&msg);
 ///
 ///     // Full error chain format:
 ///     // AppError: Config error
@@ -151,9 +232,8 @@ const _: () = {
 /// }
 /// ```
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Backtrace {
-    entries: Vec<BakctraceEntry>,
+    entries: Entries,
 }
 
 impl fmt::Debug for Backtrace {
@@ -168,7 +248,7 @@ impl Backtrace {
     #[inline]
     pub const fn new() -> Self {
         Self {
-            entries: Vec::new(),
+            entries: Entries::new(),
         }
     }
 
@@ -176,51 +256,179 @@ impl Backtrace {
     pub fn with_head<E: Error>(source: &E) -> Backtrace {
         fn inner(type_name: &'static str, display: String) -> Backtrace {
             Backtrace {
-                entries: vec![BakctraceEntry::Message(Message::new(type_name, display))],
+                entries: vec![BakctraceEntry::Message(Message::new(
+                    type_name,
+                    Some(display),
+                    None,
+                ))]
+                .into(),
             }
         }
 
+        if capture_level() == CaptureLevel::Off {
+            return Backtrace::new();
+        }
+
         let type_name = any::type_name::<E>();
         let display = source.to_string();
 
         inner(type_name, display)
     }
 
+    /// Like [`with_head`](Self::with_head), but the display message of the head
+    /// is resolved later from the error that owns this backtrace.
+    #[doc(hidden)]
+    pub fn with_lazy_head<E: Error>(_source: &E) -> Backtrace {
+        if capture_level() == CaptureLevel::Off {
+            return Backtrace::new();
+        }
+
+        Backtrace {
+            entries: vec![BakctraceEntry::Message(Message::new(
+                any::type_name::<E>(),
+                None,
+                None,
+            ))]
+            .into(),
+        }
+    }
+
+    /// Returns `true` if an error pushed now should be recorded, according to
+    /// the [capture level](crate::capture::CaptureLevel).
+    pub(crate) fn records_error(&self) -> bool {
+        match capture_level() {
+            CaptureLevel::Off => false,
+            CaptureLevel::Origin => self.is_origin(),
+            CaptureLevel::Full => true,
+        }
+    }
+
+    /// Returns `true` if no error was recorded yet, so an error pushed now is
+    /// the origin of the chain.
+    ///
+    /// Only the head of a std source, a message without locations, is recorded
+    /// before the origin, so at most the last two entries are looked at.
+    fn is_origin(&self) -> bool {
+        let mut entries = self.entries.iter_rev();
+
+        match (entries.next(), entries.next()) {
+            (None, _) | (Some(BakctraceEntry::Message(_)), None) => true,
+            (Some(_), _) => false,
+        }
+    }
+
     pub(crate) fn push_error(
         &mut self,
         type_name: &'static str,
-        display: String,
+        display: Option<String>,
+        public: Option<&'static str>,
         location: Location,
     ) {
-        self.entries
-            .push(BakctraceEntry::Message(Message::new(type_name, display)));
+        #[cfg(any(feature = "std-backtrace", feature = "tracing"))]
+        let is_origin = self.is_origin();
+
+        #[cfg_attr(
+            not(any(feature = "std-backtrace", feature = "tracing")),
+            expect(unused_mut)
+        )]
+        let mut message = Message::new(type_name, display, public);
+
+        #[cfg(feature = "std-backtrace")]
+        if is_origin && let Some(stack) = crate::capture::std_backtrace() {
+            message.set_stack(stack);
+        }
+
+        #[cfg(feature = "tracing")]
+        if is_origin && let Some(spans) = crate::trace::span_trace() {
+            message.set_spans(spans);
+        }
+
+        self.entries.push(BakctraceEntry::Message(message));
 
         self.entries
             .push(BakctraceEntry::Locations(DoubleLocations::new(location)));
     }
 
-    pub(crate) const fn head_and_entries(&self) -> (Option<&Message>, &[BakctraceEntry]) {
-        let entries = self.entries.as_slice();
+    pub(crate) fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the messages of the chain, from the innermost error to the outermost.
+    pub(crate) fn messages(&self) -> impl Iterator<Item = &Message> {
+        self.entries.iter().filter_map(|entry| match entry {
+            BakctraceEntry::Message(message) => Some(message),
+            BakctraceEntry::Locations(_)
+            | BakctraceEntry::Attachment(_)
+            | BakctraceEntry::Child(_)
+            | BakctraceEntry::Suppressed(_)
+            | BakctraceEntry::Repeated(_)
+            | BakctraceEntry::Elided(_) => None,
+        })
+    }
+
+    pub(crate) fn message_count(&self) -> usize {
+        self.messages().count()
+    }
+
+    pub(crate) fn has_pending_display(&self) -> bool {
+        self.entries.has_pending()
+    }
+
+    /// Resolves pending display messages, `displays` are ordered from the
+    /// innermost error to the outermost, in the same order as the messages.
+    pub(crate) fn resolve_displays(&self, displays: &[&dyn fmt::Display]) {
+        let mut displays = displays.iter();
+        let mut pending = false;
+
+        for message in self.messages() {
+            let display = displays.next();
+
+            if message.is_pending() {
+                match display {
+                    Some(display) => message.resolve(*display),
+                    None => pending = true,
+                }
+            }
+        }
+
+        self.entries.set_pending(pending);
+    }
+
+    pub(crate) fn head_and_entries(
+        &self,
+    ) -> (Option<&Message>, impl Iterator<Item = &BakctraceEntry>) {
+        let mut entries = self.entries.iter();
+        let mut peek = self.entries.iter();
 
-        match entries {
-            [] => (None, &[]),
-            [BakctraceEntry::Locations(_), ..] => unreachable!(),
-            [BakctraceEntry::Message(first), rest @ ..] => match rest {
-                [] => (Some(first), &[]),
-                [BakctraceEntry::Message(_second), ..] => (Some(first), rest),
-                [BakctraceEntry::Locations(_second), ..] => (None, entries),
-            },
+        match (peek.next(), peek.next()) {
+            (Some(BakctraceEntry::Locations(_)), _) => unreachable!(),
+            (Some(BakctraceEntry::Message(first)), None | Some(BakctraceEntry::Message(_))) => {
+                entries.next();
+                (Some(first), entries)
+            }
+            _ => (None, entries),
         }
     }
 
     pub(crate) fn push_location(&mut self, location: Location) {
-        debug_assert!(matches!(
-            self.entries.first(),
-            Some(BakctraceEntry::Message(_))
-        ));
+        if capture_level() != CaptureLevel::Full {
+            return;
+        }
 
-        let entry = self
-            .entries
+        if !self.has_error() {
+            return;
+        }
+
+        if self.collapse_repeated(location) {
+            return;
+        }
+
+        if self.frame_location_count() >= max_locations() {
+            self.elide_last_location();
+        }
+
+        let entries = self.entries.tail_mut(1);
+        let entry = entries
             .last_mut()
             .expect("there is must at least one message entry");
 
@@ -229,11 +437,513 @@ impl Backtrace {
                 let l = locations.push(location);
                 debug_assert!(l.is_none());
             }
-            BakctraceEntry::Message(_) | BakctraceEntry::Locations(_) => {
-                self.entries
-                    .push(BakctraceEntry::Locations(DoubleLocations::new(location)));
+            BakctraceEntry::Message(_)
+            | BakctraceEntry::Locations(_)
+            | BakctraceEntry::Attachment(_)
+            | BakctraceEntry::Child(_)
+            | BakctraceEntry::Suppressed(_)
+            | BakctraceEntry::Repeated(_)
+            | BakctraceEntry::Elided(_) => {
+                entries.push(BakctraceEntry::Locations(DoubleLocations::new(location)));
+            }
+        }
+    }
+
+    /// Counts `location` as a repeat if it is the same as the last recorded location.
+    fn collapse_repeated(&mut self, location: Location) -> bool {
+        match self.entries.last() {
+            Some(BakctraceEntry::Locations(_) | BakctraceEntry::Repeated(_)) => {}
+            _ => return false,
+        }
+
+        let entries = self.entries.tail_mut(2);
+
+        match entries.as_mut_slice() {
+            [
+                ..,
+                BakctraceEntry::Locations(locations),
+                BakctraceEntry::Repeated(count),
+            ] if locations.last() == LocationRef::Local(location, Stamp::none()) => {
+                *count = count.saturating_add(1);
+                true
+            }
+            [.., BakctraceEntry::Locations(locations)]
+                if locations.last() == LocationRef::Local(location, Stamp::none()) =>
+            {
+                entries.push(BakctraceEntry::Repeated(1));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the number of locations recorded for the last error.
+    fn frame_location_count(&self) -> usize {
+        self.entries
+            .iter_rev()
+            .take_while(|entry| !matches!(entry, BakctraceEntry::Message(_)))
+            .map(|entry| match entry {
+                BakctraceEntry::Locations(locations) => locations.len(),
+                BakctraceEntry::Message(_)
+                | BakctraceEntry::Attachment(_)
+                | BakctraceEntry::Child(_)
+                | BakctraceEntry::Suppressed(_)
+                | BakctraceEntry::Repeated(_)
+                | BakctraceEntry::Elided(_) => 0,
+            })
+            .sum()
+    }
+
+    /// Drops the most recent location, and counts it in an elided marker.
+    ///
+    /// Only called when the last error has at least two locations, so its
+    /// first location is never dropped.
+    fn elide_last_location(&mut self) {
+        let entries = self.entries.tail_mut(3);
+        let mut elided = 0u32;
+
+        if let Some(BakctraceEntry::Repeated(count)) = entries.last() {
+            elided = *count;
+            entries.pop();
+        }
+
+        match entries.last_mut() {
+            Some(BakctraceEntry::Locations(locations)) => {
+                if !locations.pop() {
+                    entries.pop();
+                }
+                elided = elided.saturating_add(1);
+            }
+            // locations after a note or a child are kept, they cannot be merged
+            _ => {
+                if elided > 0 {
+                    entries.push(BakctraceEntry::Repeated(elided));
+                }
+                return;
+            }
+        }
+
+        match entries.last_mut() {
+            Some(BakctraceEntry::Elided(count)) => *count = count.saturating_add(elided),
+            _ => entries.push(BakctraceEntry::Elided(elided)),
+        }
+    }
+
+    /// Compacts the backtrace, keeping only the first and the last location of
+    /// each error.
+    ///
+    /// Use it at API boundaries, where the path an error took inside a component
+    /// is no longer interesting. Messages, notes and nested backtraces are kept,
+    /// the dropped locations are counted in a `… N more locations elided` line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use error2::{Location, prelude::*};
+    ///
+    /// let mut err = ViaRoot("connection reset").build();
+    /// for line in 1..=5 {
+    ///     err = err.attach_location(Location::new("src/actor.rs", line, 1));
+    /// }
+    ///
+    /// err.backtrace_mut().squash();
+    ///
+    /// let msg = err.backtrace().error_message();
+    /// # let msg = error2::strip_stamps(&msg);
+    /// assert_eq!(msg.matches("\n    at ").count(), 2);
+    /// assert!(msg.ends_with("\n    … 4 more locations elided\n    at src/actor.rs:5:1"));
+    /// ```
+    pub fn squash(&mut self) {
+        fn flush(entries: &mut Vec<BakctraceEntry>, group: &mut Vec<BakctraceEntry>) {
+            let mut locations = 0usize;
+            let mut dropped = 0u32;
+            let mut first = None;
+            let mut last = None;
+
+            for entry in group.iter() {
+                match entry {
+                    BakctraceEntry::Locations(dl) => {
+                        locations += dl.len();
+                        first = first.or(Some(dl.first()));
+                        last = Some(dl.last());
+                    }
+                    BakctraceEntry::Repeated(count) | BakctraceEntry::Elided(count) => {
+                        dropped = dropped.saturating_add(*count);
+                    }
+                    BakctraceEntry::Message(_)
+                    | BakctraceEntry::Attachment(_)
+                    | BakctraceEntry::Child(_)
+                    | BakctraceEntry::Suppressed(_) => unreachable!(),
+                }
+            }
+
+            match (first, last) {
+                (Some(first), Some(last)) if locations > 2 || dropped > 0 => {
+                    entries.push(BakctraceEntry::Locations(DoubleLocations::from_ref(first)));
+
+                    if locations == 1 {
+                        // a single location that was repeated
+                        entries.push(BakctraceEntry::Repeated(dropped));
+                    } else {
+                        let elided = u32::try_from(locations - 2)
+                            .unwrap_or(u32::MAX)
+                            .saturating_add(dropped);
+
+                        entries.push(BakctraceEntry::Elided(elided));
+                        entries.push(BakctraceEntry::Locations(DoubleLocations::from_ref(last)));
+                    }
+                    group.clear();
+                }
+                _ => entries.append(group),
             }
         }
+
+        let old = mem::take(&mut self.entries).into_vec();
+        let mut entries = Vec::new();
+        let mut group = Vec::new();
+
+        for entry in old {
+            match entry {
+                BakctraceEntry::Locations(_)
+                | BakctraceEntry::Repeated(_)
+                | BakctraceEntry::Elided(_) => group.push(entry),
+                BakctraceEntry::Message(_)
+                | BakctraceEntry::Attachment(_)
+                | BakctraceEntry::Child(_)
+                | BakctraceEntry::Suppressed(_) => {
+                    flush(&mut entries, &mut group);
+                    entries.push(entry);
+                }
+            }
+        }
+
+        flush(&mut entries, &mut group);
+
+        self.entries = entries.into();
+    }
+
+    /// Returns `true` if the backtrace starts with a recorded error, which the
+    /// locations, attachments and other errors pushed after it belong to.
+    ///
+    /// It does not if the error was created while the capture level was `Off`
+    /// and the level changed since, or if it was decoded from a peer that sent
+    /// notes before the first error.
+    fn has_error(&self) -> bool {
+        matches!(self.entries.first(), Some(BakctraceEntry::Message(_)))
+    }
+
+    /// Returns `true` if an attachment or another error's backtrace pushed now
+    /// should be recorded.
+    fn records_entries(&self) -> bool {
+        capture_level() != CaptureLevel::Off && self.has_error()
+    }
+
+    pub(crate) fn push_child(&mut self, child: Backtrace) {
+        if !self.records_entries() {
+            return;
+        }
+
+        self.entries.push(BakctraceEntry::Child(Box::new(child)));
+    }
+
+    pub(crate) fn push_suppressed(&mut self, suppressed: Backtrace) {
+        if !self.records_entries() {
+            return;
+        }
+
+        self.entries
+            .push(BakctraceEntry::Suppressed(Box::new(suppressed)));
+    }
+
+    /// Returns the backtraces of all suppressed errors, in the order they were suppressed.
+    ///
+    /// See [`Suppress`](crate::Suppress) for details.
+    pub fn suppressed(&self) -> impl Iterator<Item = &Backtrace> {
+        self.entries.iter().filter_map(|entry| match entry {
+            BakctraceEntry::Suppressed(backtrace) => Some(&**backtrace),
+            BakctraceEntry::Message(_)
+            | BakctraceEntry::Locations(_)
+            | BakctraceEntry::Attachment(_)
+            | BakctraceEntry::Child(_)
+            | BakctraceEntry::Repeated(_)
+            | BakctraceEntry::Elided(_) => None,
+        })
+    }
+
+    /// Attaches a typed value that is hidden from the rendered error message.
+    ///
+    /// The value can later be retrieved with [`request_ref`](Self::request_ref),
+    /// without knowing the concrete error type that carries this backtrace.
+    ///
+    /// Nothing is attached at the `Off` [capture level](crate::capture::CaptureLevel),
+    /// or if the backtrace has not recorded an error.
+    ///
+    /// Hidden values are opaque, backtraces compare them by their type only.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use error2::prelude::*;
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct TenantId(u64);
+    ///
+    /// let mut err = ViaRoot("tenant disabled").build();
+    /// err.backtrace_mut().attach_value(TenantId(7));
+    ///
+    /// assert_eq!(err.backtrace().request_ref::<TenantId>(), Some(&TenantId(7)));
+    /// assert!(!err.backtrace().error_message().contains("TenantId"));
+    /// ```
+    pub fn attach_value<T: Send + Sync + 'static>(&mut self, value: T) {
+        if !self.records_entries() {
+            return;
+        }
+
+        self.entries
+            .push(BakctraceEntry::Attachment(Box::new(Attachment::hidden(
+                value,
+            ))));
+    }
+
+    /// Attaches a typed value that is shown in the rendered error message.
+    ///
+    /// The value is rendered as a `note:` line below the locations that were
+    /// recorded before it, and can be retrieved with [`request_ref`](Self::request_ref).
+    ///
+    /// Like [`attach_value`](Self::attach_value), nothing is attached at the
+    /// `Off` capture level.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::fmt;
+    ///
+    /// use error2::prelude::*;
+    ///
+    /// #[derive(Debug)]
+    /// struct RequestId(u64);
+    ///
+    /// impl fmt::Display for RequestId {
+    ///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    ///         write!(f, "request id: {}", self.0)
+    ///     }
+    /// }
+    ///
+    /// let mut err = ViaRoot("timeout").build();
+    /// err.backtrace_mut().attach_printable(RequestId(42));
+    ///
+    /// assert_eq!(err.backtrace().request_ref::<RequestId>().unwrap().0, 42);
+    /// assert!(
+    ///     err.backtrace()
+    ///         .error_message()
+    ///         .ends_with("\n    note: request id: 42")
+    /// );
+    /// ```
+    pub fn attach_printable<T: fmt::Display + Send + Sync + 'static>(&mut self, value: T) {
+        if !self.records_entries() {
+            return;
+        }
+
+        self.entries
+            .push(BakctraceEntry::Attachment(Box::new(Attachment::printable(
+                value,
+            ))));
+    }
+
+    /// Returns the most recently attached value of type `T`, if any.
+    ///
+    /// Values attached anywhere along an `Error2` chain share the same backtrace,
+    /// so they can be requested from any outer error, including [`BoxedError2`](crate::BoxedError2).
+    ///
+    /// Typed values do not survive serialization; after a round trip only the
+    /// rendered text of displayable attachments is kept.
+    pub fn request_ref<T: 'static>(&self) -> Option<&T> {
+        self.request_refs().next()
+    }
+
+    /// Returns all attached values of type `T`, the most recently attached first.
+    pub fn request_refs<T: 'static>(&self) -> impl Iterator<Item = &T> {
+        self.entries.iter_rev().filter_map(|entry| match entry {
+            BakctraceEntry::Attachment(attachment) => attachment.downcast_ref::<T>(),
+            BakctraceEntry::Message(_)
+            | BakctraceEntry::Locations(_)
+            | BakctraceEntry::Child(_)
+            | BakctraceEntry::Suppressed(_)
+            | BakctraceEntry::Repeated(_)
+            | BakctraceEntry::Elided(_) => None,
+        })
+    }
+
+    /// Returns the nearest user-facing message along the error chain.
+    ///
+    /// Frames are searched from the outermost error inwards, and the first
+    /// message set with `#[error2(public = "...")]` is returned. Internal display
+    /// strings are never returned by this method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use error2::prelude::*;
+    ///
+    /// #[derive(Debug, Error2)]
+    /// pub enum ProfileError {
+    ///     #[error2(
+    ///         display("cannot read /var/lib/app/{user}.json"),
+    ///         public = "Could not load your profile"
+    ///     )]
+    ///     Read {
+    ///         user: String,
+    ///         source: std::io::Error,
+    ///         backtrace: Backtrace,
+    ///     },
+    /// }
+    ///
+    /// #[derive(Debug, Error2)]
+    /// #[error2(display("request failed"))]
+    /// pub struct RequestError {
+    ///     source: ProfileError,
+    /// }
+    ///
+    /// fn handle() -> Result<String, RequestError> {
+    ///     std::fs::read_to_string("/var/lib/app/alice.json")
+    ///         .context(Read2 { user: "alice" })
+    ///         .context(RequestError2)
+    /// }
+    ///
+    /// let err = handle().unwrap_err();
+    ///
+    /// assert_eq!(
+    ///     err.backtrace().public_message(),
+    ///     Some("Could not load your profile")
+    /// );
+    /// assert_eq!(err.user_message(), "Could not load your profile");
+    /// ```
+    pub fn public_message(&self) -> Option<&str> {
+        self.public_messages().next()
+    }
+
+    pub(crate) fn public_messages(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter_rev().filter_map(|entry| match entry {
+            BakctraceEntry::Message(message) => message.public(),
+            BakctraceEntry::Locations(_)
+            | BakctraceEntry::Attachment(_)
+            | BakctraceEntry::Child(_)
+            | BakctraceEntry::Suppressed(_)
+            | BakctraceEntry::Repeated(_)
+            | BakctraceEntry::Elided(_) => None,
+        })
+    }
+
+    /// Returns the wall-clock time at which the innermost error was created.
+    ///
+    /// Returns `None` if nothing was recorded, or for a deserialized backtrace
+    /// that carries no time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::SystemTime;
+    ///
+    /// use error2::prelude::*;
+    ///
+    /// let before = SystemTime::now();
+    /// let err = ViaRoot("connection reset").build();
+    ///
+    /// let origin = err.backtrace().origin_time().unwrap();
+    /// assert!(origin >= before && origin <= SystemTime::now());
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "timestamps")))]
+    #[cfg(feature = "timestamps")]
+    pub fn origin_time(&self) -> Option<std::time::SystemTime> {
+        self.messages().next()?.created()
+    }
+
+    /// Returns the time between the first and the last recorded location,
+    /// that is how long the error has travelled since it was created.
+    #[cfg_attr(docsrs, doc(cfg(feature = "timestamps")))]
+    #[cfg(feature = "timestamps")]
+    pub fn elapsed(&self) -> Option<core::time::Duration> {
+        let last = self.entries.iter_rev().find_map(|entry| match entry {
+            BakctraceEntry::Locations(locations) => Some(locations.last().stamp()),
+            _ => None,
+        })?;
+
+        last.since(self.origin_stamp()?)
+    }
+
+    /// Returns when the first location was recorded, the origin of the
+    /// elapsed times.
+    #[cfg(feature = "timestamps")]
+    pub(crate) fn origin_stamp(&self) -> Option<Stamp> {
+        self.entries.iter().find_map(|entry| match entry {
+            BakctraceEntry::Locations(locations) => Some(locations.first().stamp()),
+            _ => None,
+        })
+    }
+
+    /// Returns the call stack captured where the innermost error was created.
+    ///
+    /// With the `std-backtrace` feature, a [`std::backtrace::Backtrace`] is
+    /// captured when the first location of a chain is recorded, by
+    /// [`BoxedError2::from_root`](crate::BoxedError2::from_root),
+    /// [`BoxedError2::from_std`](crate::BoxedError2::from_std) or the
+    /// constructors generated by `#[derive(Error2)]`. Capturing follows the
+    /// `RUST_LIB_BACKTRACE` and `RUST_BACKTRACE` environment variables, unless it
+    /// is [forced](crate::capture::set_force_std_backtrace).
+    ///
+    /// The stack is shown by the [extended](crate::render::Render::extended)
+    /// rendering, below the first location. It is ignored by comparisons and
+    /// not serialized.
+    #[cfg_attr(docsrs, doc(cfg(feature = "std-backtrace")))]
+    #[cfg(feature = "std-backtrace")]
+    pub fn stack_trace(&self) -> Option<&std::backtrace::Backtrace> {
+        self.messages().find_map(Message::stack)
+    }
+
+    /// Returns the `tracing` spans that were active where the innermost error
+    /// was created.
+    ///
+    /// With the `tracing` feature, the spans are captured like a
+    /// [`SpanTrace`](tracing_error::SpanTrace) when the first location of a
+    /// chain is recorded, so the subscriber needs an
+    /// [`ErrorLayer`](tracing_error::ErrorLayer). They are rendered below the
+    /// first location, the innermost span first. They are ignored by
+    /// comparisons and not serialized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use error2::prelude::*;
+    /// use tracing_error::ErrorLayer;
+    /// use tracing_subscriber::prelude::*;
+    ///
+    /// let subscriber = tracing_subscriber::registry().with(ErrorLayer::default());
+    ///
+    /// let err = tracing::subscriber::with_default(subscriber, || {
+    ///     let _request = tracing::info_span!("request", id = 7).entered();
+    ///     let _query = tracing::info_span!("query").entered();
+    ///
+    ///     ViaRoot("connection reset").build()
+    /// });
+    ///
+    /// assert!(err.backtrace().span_trace().is_some());
+    /// assert!(err.backtrace().error_message().ends_with(concat!(
+    ///     "\n    in query",
+    ///     "\n    in request{id=7}",
+    /// )));
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "tracing")))]
+    #[cfg(feature = "tracing")]
+    pub fn span_trace(&self) -> Option<&tracing_error::SpanTrace> {
+        self.messages().find_map(Message::spans)
+    }
+
+    /// Returns a configurable renderer for this backtrace.
+    ///
+    /// See [`Render`](crate::render::Render) for the available views.
+    #[inline]
+    pub fn render(&self) -> Render<'_> {
+        Render::new(self)
     }
 
     /// Returns a formatted string containing the complete error chain.
@@ -257,6 +967,7 @@ impl Backtrace {
     ///
     /// if let Err(e) = operation() {
     ///     let msg = e.backtrace().error_message();
+    /// #   let msg = error2::strip_stamps(&msg);
     ///
     ///     // Full error format with location tracking:
     ///     // TestError: test error
@@ -276,3 +987,479 @@ impl Backtrace {
         crate::extract_error_message(self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serialize_deserialize_suppressed() {
+        use crate::{BoxedError2, Error2, Suppress, extract::strip_stamps};
+
+        let primary = BoxedError2::from_root("commit failed");
+        let secondary = BoxedError2::from_root("rollback failed");
+
+        let error = primary.suppress(secondary);
+        let origin = error.backtrace();
+
+        let serialized = serde_json::to_string(origin).unwrap();
+        assert!(serialized.contains(r#""suppressed":{"version":1"#));
+        assert!(serialized.contains("rollback failed"));
+
+        let deserialized = serde_json::from_str::<super::Backtrace>(&serialized).unwrap();
+
+        assert_eq!(origin, &deserialized);
+        assert_eq!(
+            strip_stamps(&origin.error_message()),
+            strip_stamps(&deserialized.error_message())
+        );
+        assert_eq!(deserialized.suppressed().count(), 1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deserialized_locations_are_owned() {
+        use super::{BakctraceEntry, double_locations::DoubleLocations};
+        use crate::{Attach, BoxedError2, Error2, Location, extract::strip_stamps};
+
+        let error = BoxedError2::from_root("timeout")
+            .attach_location(Location::new("src/peer.rs", 1, 1))
+            .attach_location(Location::new("src/peer.rs", 2, 1));
+
+        let serialized = serde_json::to_string(error.backtrace()).unwrap();
+        let mut deserialized = serde_json::from_str::<super::Backtrace>(&serialized).unwrap();
+
+        assert!(
+            deserialized.entries.iter().all(|entry| !matches!(
+                entry,
+                BakctraceEntry::Locations(DoubleLocations::Local(..))
+            ))
+        );
+        assert!(deserialized.messages().all(|message| message.is_remote()));
+        assert_eq!(error.backtrace(), &deserialized);
+        assert_eq!(serde_json::to_string(&deserialized).unwrap(), serialized);
+
+        deserialized.push_location(Location::new("src/peer.rs", 2, 1));
+        deserialized.push_location(Location::new("src/main.rs", 3, 1));
+
+        assert!(
+            strip_stamps(&deserialized.error_message()).ends_with(concat!(
+                "\n    at src/peer.rs:2:1 (repeated 2 times)",
+                "\n    at src/main.rs:3:1"
+            ))
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_schema_keeps_the_order_of_events() {
+        use crate::{Attach, BoxedError2, Error2, Location, extract::strip_stamps};
+
+        let mut error = BoxedError2::from_root("timeout")
+            .attach_location(Location::new("src/a.rs", 1, 1))
+            .attach_location(Location::new("src/a.rs", 1, 1))
+            .attach_location(Location::new("src/b.rs", 2, 1));
+        error.backtrace_mut().attach_printable("peer: 10.0.0.1");
+        error.backtrace_mut().attach_value(42_u32);
+        let error = error
+            .attach_location(Location::new("src/c.rs", 3, 1))
+            .attach_location(Location::new("src/d.rs", 4, 1))
+            .attach_location(Location::new("src/e.rs", 5, 1));
+
+        let serialized = serde_json::to_value(error.backtrace()).unwrap();
+        let frame = &serialized["frames"][0];
+        assert_eq!(serialized["version"], 1);
+        assert_eq!(frame["display"], "timeout");
+        // the first location is where the root error was created
+        assert_eq!(frame["locations"][1], "src/a.rs:1:1");
+        assert_eq!(frame["locations"].as_array().unwrap().len(), 6);
+        assert_eq!(
+            frame["events"],
+            serde_json::json!([
+                { "at": 2, "event": { "repeated": 1 } },
+                { "at": 3, "event": { "note": "peer: 10.0.0.1" } },
+            ])
+        );
+
+        let deserialized = serde_json::from_value::<super::Backtrace>(serialized).unwrap();
+        assert_eq!(
+            strip_stamps(&deserialized.error_message()),
+            strip_stamps(&error.backtrace().error_message())
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_schema_rejects_unknown_versions() {
+        use alloc::string::ToString;
+
+        let error =
+            serde_json::from_str::<super::Backtrace>(r#"{"version":2,"frames":[]}"#).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("unsupported backtrace version 2")
+        );
+
+        let error = serde_json::from_str::<super::Backtrace>(
+            r#"{"version":1,"frames":[{"type_name":"E","display":null,"locations":["nowhere"]}]}"#,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("invalid location `nowhere`"));
+
+        let error = serde_json::from_str::<super::Backtrace>(
+            r#"{"version":1,"events":[{"at":0,"event":{"elided":3}}],"frames":[]}"#,
+        )
+        .unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("`elided` event before the first frame")
+        );
+    }
+
+    #[cfg(all(feature = "timestamps", feature = "serde"))]
+    #[test]
+    fn test_timestamps() {
+        use std::{thread, time::Duration};
+
+        use crate::{Attach, BoxedError2, Error2, Location};
+
+        let error = BoxedError2::from_root("timeout");
+        thread::sleep(Duration::from_millis(5));
+        let error = error.attach_location(Location::new("src/peer.rs", 1, 1));
+
+        let elapsed = error.backtrace().elapsed().unwrap();
+        assert!(elapsed >= Duration::from_millis(5));
+
+        let msg = error.backtrace().error_message();
+        // with the `threads` feature, the thread comes after the delta
+        let without_threads = regex::Regex::new(r" \[thread [^\]]+\]")
+            .unwrap()
+            .replace_all(&msg, "");
+        let re = regex::Regex::new(concat!(
+            r"\n    at [^\n]+:\d+:\d+",
+            r"\n    at src/peer\.rs:1:1 \(\+\d+\.\dms\)$",
+        ))
+        .unwrap();
+        assert!(re.is_match(&without_threads), "{}", msg);
+
+        let serialized = serde_json::to_value(error.backtrace()).unwrap();
+        assert!(serialized["origin_unix_ns"].is_u64());
+        assert_eq!(serialized["frames"][0]["elapsed_ns"][0], 0);
+
+        let deserialized = serde_json::from_value::<super::Backtrace>(serialized).unwrap();
+        assert_eq!(deserialized.elapsed(), Some(elapsed));
+        assert_eq!(deserialized.origin_time(), error.backtrace().origin_time());
+        assert_eq!(*deserialized.error_message(), *without_threads);
+
+        assert_eq!(
+            crate::extract::format_delta(Duration::from_nanos(999)),
+            "+999ns"
+        );
+        assert_eq!(
+            crate::extract::format_delta(Duration::from_micros(1500)),
+            "+1.5ms"
+        );
+        assert_eq!(
+            crate::extract::format_delta(Duration::from_secs(90)),
+            "+90.0s"
+        );
+    }
+
+    #[test]
+    fn test_strip_stamps() {
+        use crate::extract::strip_stamps;
+
+        let msg = concat!(
+            "Error: timeout",
+            "\n    at src/a.rs:1:1 [thread 'main' #1]",
+            "\n    at src/b.rs:2:1 (+1.5ms) [thread 'worker' #2]",
+            "\n    at src/c.rs:3:1 (+90.0s)",
+            "\n    at src/d.rs:4:1 (unclosed",
+        );
+
+        assert_eq!(
+            strip_stamps(msg),
+            concat!(
+                "Error: timeout",
+                "\n    at src/a.rs:1:1",
+                "\n    at src/b.rs:2:1",
+                "\n    at src/c.rs:3:1",
+                "\n    at src/d.rs:4:1 (unclosed",
+            )
+        );
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn test_threads_are_shown_when_they_change() {
+        use std::thread;
+
+        use crate::{Attach, BoxedError2, Error2, Location};
+
+        let error = BoxedError2::from_root("timeout")
+            .attach_location(Location::new("src/a.rs", 1, 1))
+            .attach_location(Location::new("src/b.rs", 2, 1));
+
+        let error = thread::Builder::new()
+            .name("worker".into())
+            .spawn(move || error.attach_location(Location::new("src/c.rs", 3, 1)))
+            .unwrap()
+            .join()
+            .unwrap();
+
+        let error = error.attach_location(Location::new("src/d.rs", 4, 1));
+
+        let msg = error.backtrace().error_message();
+        // with the `timestamps` feature, the delta comes before the thread
+        let without_deltas = regex::Regex::new(r" \(\+[^)]+\)")
+            .unwrap()
+            .replace_all(&msg, "");
+        let re = regex::Regex::new(concat!(
+            r"\n    at [^\n]+ \[thread '[^']+' #\d+\]",
+            r"\n    at src/a\.rs:1:1",
+            r"\n    at src/b\.rs:2:1",
+            r"\n    at src/c\.rs:3:1 \[thread 'worker' #\d+\]",
+            r"\n    at src/d\.rs:4:1 \[thread '[^']+' #\d+\]$",
+        ))
+        .unwrap();
+        assert!(re.is_match(&without_deltas), "{}", msg);
+    }
+
+    #[cfg(feature = "std-backtrace")]
+    #[test]
+    fn test_std_backtrace_is_captured_at_the_origin() {
+        use std::{io, string::ToString};
+
+        use crate::{Attach, BoxedError2, Error2, Location};
+
+        crate::capture::set_force_std_backtrace(true);
+
+        let error = BoxedError2::from_std(io::Error::other("disk full"))
+            .attach_location(Location::new("src/store.rs", 7, 1));
+        let error = BoxedError2::from_err2(error);
+
+        // reset before asserting, other tests in this process capture lazily
+        crate::capture::set_force_std_backtrace(false);
+
+        let stacks = error
+            .backtrace()
+            .messages()
+            .filter(|message| message.stack().is_some())
+            .count();
+        assert_eq!(stacks, 1);
+        assert!(error.backtrace().stack_trace().is_some());
+
+        let msg = error.backtrace().error_message();
+        assert!(!msg.contains("stack backtrace:"));
+
+        // the stack follows the origin, before the locations attached later
+        let extended = error.backtrace().render().extended().to_string();
+        let re = regex::Regex::new(concat!(
+            r"(?s)^error2::boxed::BoxedError2: disk full",
+            r"\n    at [^\n]+",
+            r"\n    stack backtrace:\n.+",
+            r"\n    at src/store\.rs:7:1[^\n]*",
+            r"\n    at [^\n]+",
+            r"\nstd::io::error::Error: disk full$",
+        ))
+        .unwrap();
+        assert!(re.is_match(&extended), "{}", extended);
+    }
+
+    #[test]
+    fn test_clones_share_prefix() {
+        use std::hash::{BuildHasher, RandomState};
+
+        use crate::{Attach, BoxedError2, Error2, Location, extract::strip_stamps};
+
+        let mut error = BoxedError2::from_root("timeout");
+        for line in 1..=3 {
+            error = error.attach_location(Location::new("src/retry.rs", line, 1));
+        }
+
+        let mut fan_out = error.backtrace().clone();
+        assert_eq!(&fan_out, error.backtrace());
+
+        fan_out.push_location(Location::new("src/waiter.rs", 1, 1));
+        fan_out.push_location(Location::new("src/waiter.rs", 1, 1));
+        error = error.attach_location(Location::new("src/main.rs", 9, 1));
+
+        let msg = strip_stamps(&error.backtrace().error_message());
+        assert!(msg.ends_with("\n    at src/retry.rs:3:1\n    at src/main.rs:9:1"));
+        assert!(!msg.contains("waiter"));
+        assert!(
+            strip_stamps(&fan_out.error_message()).ends_with(
+                "\n    at src/retry.rs:3:1\n    at src/waiter.rs:1:1 (repeated 2 times)"
+            )
+        );
+
+        // a chunked backtrace equals and hashes like one stored in a single chunk
+        let flat = super::Backtrace {
+            entries: fan_out.entries.clone().into_vec().into(),
+        };
+        let state = RandomState::new();
+        assert_eq!(fan_out, flat);
+        assert_eq!(state.hash_one(&fan_out), state.hash_one(&flat));
+        assert_eq!(fan_out.cmp(error.backtrace()), flat.cmp(error.backtrace()));
+    }
+
+    #[test]
+    fn test_pending_displays_are_tracked() {
+        use alloc::string::ToString;
+
+        use crate::Location;
+
+        let location = Location::new("src/main.rs", 1, 1);
+
+        let mut backtrace = super::Backtrace::new();
+        backtrace.push_error("Inner", Some("inner".to_string()), None, location);
+        assert!(!backtrace.has_pending_display());
+
+        backtrace.push_error("Outer", None, None, location);
+        assert!(backtrace.has_pending_display());
+
+        // a clone pushing on top of the shared entries keeps the flag
+        let mut clone = backtrace.clone();
+        clone.push_location(Location::new("src/main.rs", 2, 1));
+        assert!(clone.has_pending_display());
+
+        clone.resolve_displays(&[&"inner", &"outer"]);
+        assert!(!clone.has_pending_display());
+
+        backtrace.resolve_displays(&[&"inner"]);
+        assert!(backtrace.has_pending_display());
+        backtrace.resolve_displays(&[&"inner", &"outer"]);
+        assert!(!backtrace.has_pending_display());
+        assert_eq!(
+            backtrace.messages().last().unwrap().display(),
+            Some("outer")
+        );
+    }
+
+    #[test]
+    fn test_entries_need_a_recorded_error() {
+        use alloc::{boxed::Box, vec};
+
+        use super::{Attachment, Backtrace, BakctraceEntry};
+        use crate::Location;
+
+        let location = Location::new("src/main.rs", 1, 1);
+
+        // created while the capture level was `Off`, propagated after it changed
+        let mut backtrace = Backtrace::new();
+        backtrace.push_location(location);
+        backtrace.attach_value(42_u32);
+        backtrace.attach_printable("peer: 10.0.0.1");
+        backtrace.push_child(Backtrace::new());
+        backtrace.push_suppressed(Backtrace::new());
+        assert!(backtrace.is_empty());
+
+        // decoded from a peer that sent a note before the first error
+        let note = BakctraceEntry::Attachment(Box::new(Attachment::note("note".into())));
+        let mut backtrace = Backtrace {
+            entries: vec![note].into(),
+        };
+        backtrace.push_location(location);
+        backtrace.attach_value(42_u32);
+        assert_eq!(backtrace.entries.iter().count(), 1);
+    }
+
+    #[test]
+    fn test_hidden_attachments_compare_by_type() {
+        use crate::{BoxedError2, Error2};
+
+        let attached = |attach: fn(&mut super::Backtrace)| {
+            let mut error = BoxedError2::from_root("tenant disabled");
+            attach(error.backtrace_mut());
+            error.backtrace().clone()
+        };
+
+        let first = attached(|backtrace| backtrace.attach_value(7_u64));
+        let second = attached(|backtrace| backtrace.attach_value(8_u64));
+        let other = attached(|backtrace| backtrace.attach_value(7_u32));
+
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+        assert_ne!(
+            first,
+            attached(|backtrace| backtrace.attach_printable(7_u64))
+        );
+    }
+
+    #[test]
+    fn test_markers_before_the_first_error_are_skipped() {
+        use alloc::vec;
+
+        use super::{Backtrace, BakctraceEntry};
+
+        // only a backtrace received from a peer can start with them
+        let backtrace = Backtrace {
+            entries: vec![BakctraceEntry::Elided(3), BakctraceEntry::Repeated(1)].into(),
+        };
+        assert!(backtrace.error_message().is_empty());
+    }
+
+    #[test]
+    fn test_children_share_entries() {
+        use alloc::{vec, vec::Vec};
+
+        use super::BakctraceEntry;
+        use crate::{BoxedError2, Error2, Errors};
+
+        let errors = Errors::new(vec![
+            BoxedError2::from_root("invalid port"),
+            BoxedError2::from_root("invalid host"),
+        ])
+        .unwrap();
+
+        let children = errors
+            .backtrace()
+            .entries
+            .iter()
+            .filter_map(|entry| match entry {
+                BakctraceEntry::Child(child) => Some(child),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(children.len(), 2);
+        for (child, error) in children.iter().zip(&errors) {
+            assert!(
+                child
+                    .entries
+                    .shares_entries_with(&error.backtrace().entries)
+            );
+        }
+
+        assert!(Errors::<BoxedError2>::new(vec![]).is_none());
+    }
+
+    #[test]
+    fn test_collapse_repeated_locations() {
+        use crate::{Attach, BoxedError2, Error2, Location, extract::strip_stamps};
+
+        let retry = Location::new("src/retry.rs", 10, 5);
+        let mut error = BoxedError2::from_root("timeout");
+
+        for _ in 0..5 {
+            error = error.attach_location(retry);
+        }
+        error = error.attach_location(Location::new("src/main.rs", 3, 1));
+
+        let msg = strip_stamps(&error.backtrace().error_message());
+        assert!(msg.ends_with(concat!(
+            "\n    at src/retry.rs:10:5 (repeated 5 times)",
+            "\n    at src/main.rs:3:1"
+        )));
+
+        error.backtrace_mut().squash();
+        assert_eq!(
+            strip_stamps(&error.backtrace().error_message()),
+            msg.replace(
+                "\n    at src/retry.rs:10:5 (repeated 5 times)",
+                "\n    … 5 more locations elided",
+            )
+        );
+    }
+}
@@ -0,0 +1,348 @@
+//! The versioned wire format of [`Backtrace`], decoupled from its in-memory layout.
+
+use alloc::{boxed::Box, string::String, vec::Vec};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error as _};
+
+use super::{
+    Backtrace, BakctraceEntry,
+    attachment::Attachment,
+    double_locations::{DoubleLocations, LocationRef, RemoteLocation},
+    message::Message,
+    stamp::Stamp,
+};
+
+/// The current version of the format, bumped on incompatible changes.
+const VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct BacktraceOut<'a> {
+    version: u32,
+    /// The wall-clock time the innermost error was created at, in nanoseconds
+    /// since the Unix epoch.
+    #[cfg(feature = "timestamps")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    origin_unix_ns: Option<u64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    events: Vec<EventOut<'a>>,
+    frames: Vec<FrameOut<'a>>,
+}
+
+#[derive(Serialize)]
+struct FrameOut<'a> {
+    type_name: &'a str,
+    display: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    public: Option<&'a str>,
+    locations: Vec<LocationOut<'a>>,
+    /// The time since the first location of the backtrace, for each location.
+    #[cfg(feature = "timestamps")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    elapsed_ns: Vec<Option<u64>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    events: Vec<EventOut<'a>>,
+}
+
+#[cfg(feature = "timestamps")]
+fn as_nanos(duration: core::time::Duration) -> u64 {
+    u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX)
+}
+
+struct LocationOut<'a>(LocationRef<'a>);
+
+impl Serialize for LocationOut<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(&self.0)
+    }
+}
+
+#[derive(Serialize)]
+struct EventOut<'a> {
+    at: usize,
+    event: EventKindOut<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum EventKindOut<'a> {
+    Repeated(u32),
+    Elided(u32),
+    Note(&'a str),
+    Child(&'a Backtrace),
+    Suppressed(&'a Backtrace),
+}
+
+impl Serialize for Backtrace {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut out = BacktraceOut {
+            version: VERSION,
+            #[cfg(feature = "timestamps")]
+            origin_unix_ns: self
+                .origin_time()
+                .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(as_nanos),
+            events: Vec::new(),
+            frames: Vec::new(),
+        };
+
+        #[cfg(feature = "timestamps")]
+        let origin = self.origin_stamp();
+
+        for entry in self.entries.iter() {
+            let (events, at) = match out.frames.last_mut() {
+                Some(frame) => (&mut frame.events, frame.locations.len()),
+                None => (&mut out.events, 0),
+            };
+
+            let event = match entry {
+                BakctraceEntry::Message(message) => {
+                    out.frames.push(FrameOut {
+                        type_name: message.type_name(),
+                        display: message.display(),
+                        public: message.public(),
+                        locations: Vec::new(),
+                        #[cfg(feature = "timestamps")]
+                        elapsed_ns: Vec::new(),
+                        events: Vec::new(),
+                    });
+                    continue;
+                }
+                BakctraceEntry::Locations(locations) => {
+                    let frame = out.frames.last_mut().ok_or_else(|| {
+                        serde::ser::Error::custom("locations recorded before any error")
+                    })?;
+                    frame.locations.extend(locations.iter().map(LocationOut));
+
+                    #[cfg(feature = "timestamps")]
+                    frame.elapsed_ns.extend(
+                        locations
+                            .iter()
+                            .map(|location| location.stamp().since(origin?).map(as_nanos)),
+                    );
+
+                    continue;
+                }
+                BakctraceEntry::Attachment(attachment) => match attachment.display() {
+                    Some(display) => EventKindOut::Note(display),
+                    // typed values cannot be serialized
+                    None => continue,
+                },
+                BakctraceEntry::Child(child) => EventKindOut::Child(child),
+                BakctraceEntry::Suppressed(suppressed) => EventKindOut::Suppressed(suppressed),
+                BakctraceEntry::Repeated(count) => EventKindOut::Repeated(*count),
+                BakctraceEntry::Elided(count) => EventKindOut::Elided(*count),
+            };
+
+            events.push(EventOut { at, event });
+        }
+
+        #[cfg(feature = "timestamps")]
+        for frame in &mut out.frames {
+            if frame.elapsed_ns.iter().all(Option::is_none) {
+                frame.elapsed_ns.clear();
+            }
+        }
+
+        out.serialize(serializer)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Backtrace")]
+struct BacktraceIn {
+    version: u32,
+    #[cfg(feature = "timestamps")]
+    #[serde(default)]
+    origin_unix_ns: Option<u64>,
+    #[serde(default)]
+    events: Vec<EventIn>,
+    frames: Vec<FrameIn>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Frame")]
+struct FrameIn {
+    type_name: String,
+    display: Option<String>,
+    #[serde(default)]
+    public: Option<String>,
+    locations: Vec<String>,
+    #[cfg(feature = "timestamps")]
+    #[serde(default)]
+    elapsed_ns: Vec<Option<u64>>,
+    #[serde(default)]
+    events: Vec<EventIn>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Event")]
+struct EventIn {
+    at: usize,
+    event: EventKindIn,
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "EventKind", rename_all = "snake_case")]
+enum EventKindIn {
+    Repeated(u32),
+    Elided(u32),
+    Note(Box<str>),
+    Child(Backtrace),
+    Suppressed(Backtrace),
+}
+
+impl EventKindIn {
+    fn name(&self) -> &'static str {
+        match self {
+            EventKindIn::Repeated(_) => "repeated",
+            EventKindIn::Elided(_) => "elided",
+            EventKindIn::Note(_) => "note",
+            EventKindIn::Child(_) => "child",
+            EventKindIn::Suppressed(_) => "suppressed",
+        }
+    }
+
+    fn into_entry(self) -> BakctraceEntry {
+        match self {
+            EventKindIn::Repeated(count) => BakctraceEntry::Repeated(count),
+            EventKindIn::Elided(count) => BakctraceEntry::Elided(count),
+            EventKindIn::Note(display) => {
+                BakctraceEntry::Attachment(Box::new(Attachment::note(display)))
+            }
+            EventKindIn::Child(child) => BakctraceEntry::Child(Box::new(child)),
+            EventKindIn::Suppressed(suppressed) => BakctraceEntry::Suppressed(Box::new(suppressed)),
+        }
+    }
+}
+
+/// Appends `locations` interleaved with `events`, each event is placed after
+/// the number of locations given by its `at`.
+fn push_trail<E: serde::de::Error>(
+    entries: &mut Vec<BakctraceEntry>,
+    locations: Vec<(String, Stamp)>,
+    events: Vec<EventIn>,
+) -> Result<(), E> {
+    let mut locations = locations.into_iter().enumerate().peekable();
+    let mut run: Vec<RemoteLocation> = Vec::new();
+
+    let flush = |entries: &mut Vec<BakctraceEntry>, run: &mut Vec<RemoteLocation>| {
+        // locations are recorded in pairs
+        while !run.is_empty() {
+            let rest = run.split_off(run.len().min(2));
+            let pair = core::mem::replace(run, rest);
+            entries.push(BakctraceEntry::Locations(DoubleLocations::remote(pair)));
+        }
+    };
+
+    for EventIn { at, event } in events {
+        while let Some((_, (location, stamp))) = locations.next_if(|(index, _)| *index < at) {
+            run.push(
+                RemoteLocation::parse(&location, stamp)
+                    .ok_or_else(|| E::custom(format_args!("invalid location `{}`", location)))?,
+            );
+        }
+
+        flush(entries, &mut run);
+        entries.push(event.into_entry());
+    }
+
+    for (_, (location, stamp)) in locations {
+        run.push(
+            RemoteLocation::parse(&location, stamp)
+                .ok_or_else(|| E::custom(format_args!("invalid location `{}`", location)))?,
+        );
+    }
+
+    flush(entries, &mut run);
+
+    Ok(())
+}
+
+impl<'de> Deserialize<'de> for Backtrace {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let BacktraceIn {
+            version,
+            #[cfg(feature = "timestamps")]
+            mut origin_unix_ns,
+            events,
+            frames,
+        } = BacktraceIn::deserialize(deserializer)?;
+
+        if version != VERSION {
+            return Err(D::Error::custom(format_args!(
+                "unsupported backtrace version {}, expected {}",
+                version, VERSION
+            )));
+        }
+
+        // events before the first frame have no locations to repeat or elide
+        if let Some(EventIn { event, .. }) = events.iter().find(|event| {
+            matches!(
+                event.event,
+                EventKindIn::Repeated(_) | EventKindIn::Elided(_)
+            )
+        }) {
+            return Err(D::Error::custom(format_args!(
+                "`{}` event before the first frame",
+                event.name()
+            )));
+        }
+
+        let mut entries = Vec::new();
+
+        push_trail::<D::Error>(&mut entries, Vec::new(), events)?;
+
+        for FrameIn {
+            type_name,
+            display,
+            public,
+            locations,
+            #[cfg(feature = "timestamps")]
+            elapsed_ns,
+            events,
+        } in frames
+        {
+            #[allow(unused_mut)]
+            let mut message = Message::deserialized(&type_name, display, public.as_deref());
+
+            #[cfg(feature = "timestamps")]
+            // the first frame is the innermost error
+            if let Some(origin) = origin_unix_ns.take() {
+                message
+                    .set_created(std::time::UNIX_EPOCH + core::time::Duration::from_nanos(origin));
+            }
+
+            entries.push(BakctraceEntry::Message(message));
+
+            #[cfg(feature = "timestamps")]
+            let stamps = elapsed_ns
+                .into_iter()
+                .map(|elapsed| match elapsed {
+                    Some(elapsed) => Stamp::remote(core::time::Duration::from_nanos(elapsed)),
+                    None => Stamp::none(),
+                })
+                .chain(core::iter::repeat(Stamp::none()));
+            #[cfg(not(feature = "timestamps"))]
+            let stamps = core::iter::repeat(Stamp::none());
+
+            push_trail::<D::Error>(
+                &mut entries,
+                locations.into_iter().zip(stamps).collect(),
+                events,
+            )?;
+        }
+
+        Ok(Backtrace {
+            entries: entries.into(),
+        })
+    }
+}
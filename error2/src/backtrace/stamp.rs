@@ -0,0 +1,136 @@
+#[cfg(feature = "threads")]
+use core::fmt;
+#[cfg(feature = "threads")]
+use std::thread::{self, ThreadId};
+#[cfg(feature = "timestamps")]
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "threads")]
+use crate::StrId;
+
+/// When and on which thread a location was recorded, zero-sized without the
+/// `timestamps` and `threads` features.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Stamp {
+    #[cfg(feature = "timestamps")]
+    at: Option<At>,
+    #[cfg(feature = "threads")]
+    hop: Option<Hop>,
+}
+
+#[cfg(feature = "timestamps")]
+#[derive(Debug, Clone, Copy)]
+enum At {
+    /// Recorded in this process.
+    Local(Instant),
+    /// Deserialized, the time since the origin of the remote backtrace.
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    Remote(Duration),
+}
+
+impl Stamp {
+    pub(crate) const fn none() -> Self {
+        Self {
+            #[cfg(feature = "timestamps")]
+            at: None,
+            #[cfg(feature = "threads")]
+            hop: None,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn now() -> Self {
+        Self {
+            #[cfg(feature = "timestamps")]
+            at: Some(At::Local(Instant::now())),
+            #[cfg(feature = "threads")]
+            hop: Hop::current(),
+        }
+    }
+
+    #[cfg(all(feature = "timestamps", feature = "serde"))]
+    pub(crate) const fn remote(elapsed: Duration) -> Self {
+        Self {
+            at: Some(At::Remote(elapsed)),
+            #[cfg(feature = "threads")]
+            hop: None,
+        }
+    }
+
+    /// Returns the thread and task the location was recorded on.
+    #[cfg(feature = "threads")]
+    pub(crate) fn hop(self) -> Option<Hop> {
+        self.hop
+    }
+
+    /// Returns the time elapsed between `origin` and this stamp.
+    ///
+    /// Local and remote stamps cannot be compared, the clocks are unrelated.
+    #[cfg(feature = "timestamps")]
+    pub(crate) fn since(self, origin: Stamp) -> Option<Duration> {
+        match (self.at?, origin.at?) {
+            (At::Local(at), At::Local(origin)) => Some(at.saturating_duration_since(origin)),
+            (At::Remote(at), At::Remote(origin)) => Some(at.saturating_sub(origin)),
+            (At::Local(_), At::Remote(_)) | (At::Remote(_), At::Local(_)) => None,
+        }
+    }
+}
+
+/// The thread, and the async task if any, a location was recorded on.
+#[cfg(feature = "threads")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Hop {
+    name: Option<StrId>,
+    id: ThreadId,
+    task: Option<u64>,
+}
+
+#[cfg(feature = "threads")]
+impl Hop {
+    fn current() -> Option<Self> {
+        std::thread_local! {
+            // thread names rarely change, so they are interned once per thread
+            static CURRENT: (Option<StrId>, ThreadId) = {
+                let thread = thread::current();
+                (thread.name().map(StrId::intern), thread.id())
+            };
+        }
+
+        // `None` while the thread-locals of the thread are being destroyed
+        let (name, id) = CURRENT.try_with(|current| *current).ok()?;
+
+        Some(Self {
+            name,
+            id,
+            task: crate::capture::task_id(),
+        })
+    }
+}
+
+/// Renders as `thread 'main' #1, task 42`.
+#[cfg(feature = "threads")]
+impl fmt::Display for Hop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("thread ")?;
+
+        if let Some(name) = self.name {
+            write!(f, "'{}' ", name)?;
+        }
+
+        // `ThreadId` only exposes its number through `Debug`, as `ThreadId(1)`
+        let id = std::format!("{:?}", self.id);
+        match id
+            .strip_prefix("ThreadId(")
+            .and_then(|id| id.strip_suffix(')'))
+        {
+            Some(number) => write!(f, "#{}", number)?,
+            None => f.write_str(&id)?,
+        }
+
+        if let Some(task) = self.task {
+            write!(f, ", task {}", task)?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,14 +1,20 @@
 mod root_err;
+mod shared;
 mod std_err;
 
-use std::{
+use alloc::boxed::Box;
+use core::{
     any::TypeId,
     error::Error,
     fmt::{self, Debug, Display, Formatter},
+    mem,
 };
 
+pub use self::shared::SharedError2;
 use self::{root_err::RootErr, std_err::StdErr};
-use crate::{Backtrace, Error2, Location, kind::ErrorKind, private, transform::SourceToTarget};
+use crate::{
+    Backtrace, Error2, Location, RemoteError, kind::ErrorKind, private, transform::SourceToTarget,
+};
 
 /// Type-erased error with automatic backtrace tracking.
 ///
@@ -20,6 +26,9 @@ use crate::{Backtrace, Error2, Location, kind::ErrorKind, private, transform::So
 /// - **Type Erasure**: Store any error implementing `Error2` or `std::error::Error`
 /// - **Downcasting**: Retrieve the original error type
 /// - **Backtrace**: Access complete error chain and locations
+/// - **Thin Pointer**: One pointer wide, so `Result<(), BoxedError2>` and
+///   `Option<BoxedError2>` are also one pointer wide. Boxing an error takes
+///   two allocations, one for the error and one for its vtable pointer.
 ///
 /// # Conversion Control
 ///
@@ -61,29 +70,49 @@ use crate::{Backtrace, Error2, Location, kind::ErrorKind, private, transform::So
 /// # }
 /// ```
 pub struct BoxedError2 {
-    source: Box<dyn Error2 + Send + Sync + 'static>,
+    // The trait object is boxed once more, so that `BoxedError2` is a thin pointer.
+    // Storing the vtable in a header of a single allocation, like `anyhow`, needs
+    // unsafe pointer casts, which this crate forbids. The extra allocation is only
+    // paid on the error path, `Result<(), BoxedError2>` stays one pointer wide,
+    // see `benches/boxed.rs`.
+    source: Box<Box<dyn Error2 + Send + Sync + 'static>>,
 }
 
+const _: () = {
+    ["Size of `BoxedError2`"][mem::size_of::<BoxedError2>() - mem::size_of::<usize>()];
+    ["Size of `Option<BoxedError2>`"]
+        [mem::size_of::<Option<BoxedError2>>() - mem::size_of::<usize>()];
+    ["Size of `Result<(), BoxedError2>`"]
+        [mem::size_of::<Result<(), BoxedError2>>() - mem::size_of::<usize>()];
+};
+
 impl BoxedError2 {
     #[inline]
     const fn source_ref(&self) -> &(dyn Error + Send + Sync + 'static) {
-        &*self.source
+        &**self.source
     }
 
     #[inline]
     const fn source_mut(&mut self) -> &mut (dyn Error + Send + Sync + 'static) {
-        &mut *self.source
+        &mut **self.source
     }
 
     #[inline]
     fn source(self) -> Box<dyn Error + Send + Sync + 'static> {
-        self.source
+        *self.source
+    }
+
+    /// Returns the shared error this error boxes, if it was converted from a
+    /// [`SharedError2`] while other clones were alive.
+    #[inline]
+    fn shared(&self) -> Option<&SharedError2> {
+        self.source_ref().downcast_ref::<SharedError2>()
     }
 
     /// Checks if this is a root error (not wrapping another error).
     #[inline]
     pub fn is_root(&self) -> bool {
-        self.source_ref().is::<RootErr>()
+        self.source_ref().is::<RootErr>() || self.shared().is_some_and(SharedError2::is_root)
     }
 
     fn generic_is_root<T: Error + 'static>() -> bool {
@@ -96,12 +125,16 @@ impl BoxedError2 {
         debug_assert!(!Self::generic_is_root::<T>());
         let source = self.source_ref();
 
-        source.is::<StdErr<T>>() || source.is::<T>()
+        source.is::<StdErr<T>>()
+            || source.is::<T>()
+            || self.shared().is_some_and(SharedError2::is::<T>)
     }
 
     /// Attempts to downcast to a reference of type `T`.
     ///
-    /// Returns `Some(ErrorKind)` if the error is of type `T`.
+    /// Returns `Some(ErrorKind)` if the error is of type `T`. An error converted
+    /// from a [`SharedError2`] downcasts like the shared error, see
+    /// [`SharedError2::downcast_ref`].
     #[inline]
     pub fn downcast_ref<T: Error + 'static>(&self) -> Option<ErrorKind<&T, &Backtrace>> {
         debug_assert!(!Self::generic_is_root::<T>());
@@ -111,12 +144,17 @@ impl BoxedError2 {
             Some(ErrorKind::Std { source, backtrace })
         } else if let Some(source) = source.downcast_ref::<T>() {
             Some(ErrorKind::Err2 { source })
+        } else if let Some(shared) = self.shared() {
+            shared.downcast_ref::<T>()
         } else {
             None
         }
     }
 
     /// Attempts to downcast to a mutable reference of type `T`.
+    ///
+    /// Returns `None` for an error converted from a [`SharedError2`] while other
+    /// clones were alive, the error is not exclusively owned.
     #[inline]
     pub fn downcast_mut<T: Error + 'static>(
         &mut self,
@@ -139,6 +177,9 @@ impl BoxedError2 {
     /// Attempts to downcast to an owned value of type `T`.
     ///
     /// Returns `Ok(ErrorKind)` if successful, or `Err(self)` on failure.
+    ///
+    /// An error converted from a [`SharedError2`] while other clones were
+    /// alive is unwrapped if they have been dropped since, and fails otherwise.
     #[inline]
     pub fn downcast<T: Error + 'static>(self) -> Result<ErrorKind<T, Backtrace>, Self> {
         debug_assert!(!Self::generic_is_root::<T>());
@@ -150,6 +191,15 @@ impl BoxedError2 {
         } else if source.is::<T>() {
             let source = *self.source().downcast::<T>().unwrap();
             Ok(ErrorKind::Err2 { source })
+        } else if source.is::<SharedError2>() {
+            let shared = *self.source().downcast::<SharedError2>().unwrap();
+
+            match shared.try_into_boxed() {
+                Ok(error) => error.downcast::<T>(),
+                Err(shared) => Err(BoxedError2 {
+                    source: Box::new(Box::new(shared)),
+                }),
+            }
         } else {
             Err(self)
         }
@@ -183,7 +233,7 @@ impl Error for BoxedError2 {
         if self.is_root() {
             None
         } else {
-            Some(&*self.source)
+            Some(&**self.source)
         }
     }
 }
@@ -198,6 +248,23 @@ impl Error2 for BoxedError2 {
     fn backtrace_mut(&mut self) -> &mut Backtrace {
         self.source.backtrace_mut()
     }
+
+    #[inline]
+    fn public_message(&self) -> Option<&'static str> {
+        self.source.public_message()
+    }
+
+    #[cfg(feature = "tracing")]
+    #[inline]
+    fn event_level(&self) -> Option<crate::trace::Level> {
+        self.source.event_level()
+    }
+
+    #[cfg(feature = "tracing")]
+    #[inline]
+    fn event_type_name(&self) -> &str {
+        self.source.event_type_name()
+    }
 }
 
 impl BoxedError2 {
@@ -210,7 +277,7 @@ impl BoxedError2 {
     where
         R: Display + Debug + Send + Sync + 'static,
     {
-        Self::from_root_with_location(root, Location::caller())
+        Self::from_root_with_location(root, Location::tracked())
     }
 
     /// Creates a root error with explicit location.
@@ -219,7 +286,7 @@ impl BoxedError2 {
         R: Display + Debug + Send + Sync + 'static,
     {
         let mut error = BoxedError2 {
-            source: Box::new(RootErr::new(root)),
+            source: Box::new(Box::new(RootErr::new(root))),
         };
 
         crate::push_error(&mut error, location);
@@ -236,7 +303,7 @@ impl BoxedError2 {
     where
         T: Error + Send + Sync + 'static,
     {
-        Self::from_std_with_location(source, Location::caller())
+        Self::from_std_with_location(source, Location::tracked())
     }
 
     /// Creates from std::error::Error with explicit location.
@@ -248,11 +315,11 @@ impl BoxedError2 {
             let mut e =
                 <dyn Error + Send + Sync>::downcast::<BoxedError2>(Box::new(source)).unwrap();
 
-            e.backtrace_mut().push_location(location);
+            crate::push_location(&mut *e, location);
             *e
         } else {
             let mut error = BoxedError2 {
-                source: Box::new(StdErr::new(source)),
+                source: Box::new(Box::new(StdErr::new(source))),
             };
 
             crate::push_error(&mut error, location);
@@ -261,6 +328,19 @@ impl BoxedError2 {
         }
     }
 
+    /// Creates a `BoxedError2` from a backtrace received from another process,
+    /// see [`RemoteError`].
+    #[track_caller]
+    #[inline]
+    pub fn from_backtrace(backtrace: Backtrace) -> BoxedError2 {
+        Self::from_backtrace_with_location(backtrace, Location::tracked())
+    }
+
+    /// Creates from a received backtrace with explicit location.
+    pub fn from_backtrace_with_location(backtrace: Backtrace, location: Location) -> BoxedError2 {
+        Self::from_err2_with_location(RemoteError::new(backtrace), location)
+    }
+
     /// Creates a `BoxedError2` from an `Error2` type.
     ///
     /// Preserves the original error's backtrace.
@@ -270,7 +350,7 @@ impl BoxedError2 {
     where
         T: Error2 + Send + Sync + 'static,
     {
-        Self::from_err2_with_location(source, Location::caller())
+        Self::from_err2_with_location(source, Location::tracked())
     }
 
     /// Creates from Error2 with explicit location.
@@ -282,11 +362,25 @@ impl BoxedError2 {
             let mut e =
                 <dyn Error + Send + Sync>::downcast::<BoxedError2>(Box::new(source)).unwrap();
 
-            e.backtrace_mut().push_location(location);
+            crate::push_location(&mut *e, location);
             *e
+        } else if (&source as &(dyn Error + Send + Sync)).is::<SharedError2>() {
+            let e = <dyn Error + Send + Sync>::downcast::<SharedError2>(Box::new(source)).unwrap();
+
+            let mut e = BoxedError2::from(*e);
+            crate::push_location(&mut e, location);
+            e
+        } else if (&source as &(dyn Error + Send + Sync)).is::<RemoteError>() {
+            // the remote error is already recorded in its backtrace
+            let mut e = BoxedError2 {
+                source: Box::new(Box::new(source)),
+            };
+
+            crate::push_location(&mut e, location);
+            e
         } else {
             let mut error = BoxedError2 {
-                source: Box::new(source),
+                source: Box::new(Box::new(source)),
             };
 
             crate::push_error(&mut error, location);
@@ -1,4 +1,5 @@
-use std::{
+use alloc::boxed::Box;
+use core::{
     error::Error,
     fmt::{self, Debug, Display, Formatter},
 };
@@ -52,4 +53,11 @@ impl Error2 for RootErr {
     fn backtrace_mut(&mut self) -> &mut Backtrace {
         &mut self.backtrace
     }
+
+    /// A root error wraps no other error, it is reported as the boxed error itself.
+    #[cfg(feature = "tracing")]
+    #[inline]
+    fn event_type_name(&self) -> &str {
+        core::any::type_name::<super::BoxedError2>()
+    }
 }
@@ -0,0 +1,259 @@
+use alloc::{boxed::Box, sync::Arc};
+use core::{
+    error::Error,
+    fmt::{self, Debug, Display, Formatter},
+};
+
+use super::BoxedError2;
+use crate::{Backtrace, Error2, kind::ErrorKind};
+
+/// Cloneable type-erased error, for caches and broadcast channels.
+///
+/// `SharedError2` keeps a [`BoxedError2`] behind an [`Arc`], so it is `Clone`
+/// even if the underlying error is not. Every clone has its own backtrace:
+/// clones share the locations recorded before they were made, and locations
+/// attached afterwards only show up in the clone they were attached to.
+///
+/// # Conversion
+///
+/// - `BoxedError2` converts into `SharedError2` with [`From`], without recording a new error.
+/// - `SharedError2` converts back with [`try_into_boxed`](Self::try_into_boxed)
+///   if it is the last clone, or with [`From`] otherwise, which keeps it wrapped.
+///   A wrapped clone still answers [`BoxedError2::is`] and
+///   [`BoxedError2::downcast_ref`] for the shared error.
+///
+/// # Example
+///
+/// ```
+/// use std::io;
+///
+/// use error2::{SharedError2, kind::ErrorKind, prelude::*};
+///
+/// fn load() -> Result<String, BoxedError2> {
+///     Err(io::Error::from(io::ErrorKind::NotFound)).context(ViaStd)
+/// }
+///
+/// let cached: Result<String, SharedError2> = load().map_err(SharedError2::from);
+///
+/// let first = cached.clone().attach().unwrap_err();
+/// let second = cached.unwrap_err();
+///
+/// // the location attached to `first` is not recorded in `second`
+/// let first_msg = first.backtrace().error_message();
+/// let second_msg = second.backtrace().error_message();
+/// assert_eq!(first_msg.matches("\n    at ").count(), 2);
+/// assert_eq!(second_msg.matches("\n    at ").count(), 1);
+///
+/// match first.downcast_ref::<io::Error>() {
+///     Some(ErrorKind::Std { source, .. }) => assert_eq!(source.kind(), io::ErrorKind::NotFound),
+///     _ => unreachable!(),
+/// }
+///
+/// drop(first);
+/// let boxed = second.try_into_boxed().unwrap();
+/// assert!(boxed.is::<io::Error>());
+/// ```
+#[derive(Clone)]
+pub struct SharedError2 {
+    source: Arc<BoxedError2>,
+    backtrace: Backtrace,
+}
+
+impl SharedError2 {
+    /// Checks if this is a root error (not wrapping another error).
+    #[inline]
+    pub fn is_root(&self) -> bool {
+        self.source.is_root()
+    }
+
+    /// Checks if the shared error contains an error of type `T`.
+    #[inline]
+    pub fn is<T: Error + 'static>(&self) -> bool {
+        self.source.is::<T>()
+    }
+
+    /// Attempts to downcast to a reference of type `T`.
+    ///
+    /// The backtrace of [`ErrorKind::Std`] is the backtrace of this clone.
+    /// [`ErrorKind::Err2`] returns the error shared by all clones, so its own
+    /// backtrace stops where the error was shared. Use [`Error2::backtrace`]
+    /// on this clone for the locations attached since.
+    #[inline]
+    pub fn downcast_ref<T: Error + 'static>(&self) -> Option<ErrorKind<&T, &Backtrace>> {
+        match self.source.downcast_ref::<T>()? {
+            ErrorKind::Std { source, .. } => Some(ErrorKind::Std {
+                source,
+                backtrace: &self.backtrace,
+            }),
+            ErrorKind::Err2 { source } => Some(ErrorKind::Err2 { source }),
+        }
+    }
+
+    /// Returns the underlying [`BoxedError2`] if this is the last clone,
+    /// with the backtrace of this clone.
+    ///
+    /// Returns `Err(self)` if other clones are still alive.
+    pub fn try_into_boxed(self) -> Result<BoxedError2, Self> {
+        let SharedError2 { source, backtrace } = self;
+
+        match Arc::try_unwrap(source) {
+            Ok(mut error) => {
+                *error.backtrace_mut() = backtrace;
+                Ok(error)
+            }
+            Err(source) => Err(SharedError2 { source, backtrace }),
+        }
+    }
+}
+
+impl From<BoxedError2> for SharedError2 {
+    fn from(error: BoxedError2) -> Self {
+        // resolves lazily rendered display messages before they are shared
+        let backtrace = error.backtrace().clone();
+
+        SharedError2 {
+            source: Arc::new(error),
+            backtrace,
+        }
+    }
+}
+
+impl From<SharedError2> for BoxedError2 {
+    /// Unwraps the [`BoxedError2`] if this is the last clone, otherwise boxes
+    /// the `SharedError2` itself, without recording a new error.
+    fn from(error: SharedError2) -> Self {
+        match error.try_into_boxed() {
+            Ok(error) => error,
+            Err(error) => BoxedError2 {
+                source: Box::new(Box::new(error)),
+            },
+        }
+    }
+}
+
+impl Display for SharedError2 {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.source, f)
+    }
+}
+
+impl Debug for SharedError2 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            Debug::fmt(&self.source, f)
+        } else {
+            Display::fmt(&self.source, f)?;
+            write!(f, "\n\n")?;
+
+            let m = self.backtrace.error_message();
+            Display::fmt(&m, f)
+        }
+    }
+}
+
+impl Error for SharedError2 {
+    #[inline]
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Error::source(&*self.source)
+    }
+}
+
+impl Error2 for SharedError2 {
+    #[inline]
+    fn backtrace(&self) -> &Backtrace {
+        &self.backtrace
+    }
+
+    #[inline]
+    fn backtrace_mut(&mut self) -> &mut Backtrace {
+        &mut self.backtrace
+    }
+
+    #[inline]
+    fn public_message(&self) -> Option<&'static str> {
+        self.source.public_message()
+    }
+
+    #[cfg(feature = "tracing")]
+    #[inline]
+    fn event_level(&self) -> Option<crate::trace::Level> {
+        self.source.event_level()
+    }
+
+    #[cfg(feature = "tracing")]
+    #[inline]
+    fn event_type_name(&self) -> &str {
+        self.source.event_type_name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::fmt;
+
+    use super::SharedError2;
+    use crate::{Attach, BoxedError2, Error2, RemoteError, kind::ErrorKind};
+
+    fn locations(error: &impl Error2) -> usize {
+        error
+            .backtrace()
+            .error_message()
+            .matches("\n    at ")
+            .count()
+    }
+
+    #[test]
+    fn test_boxed_clones_keep_downcasting() {
+        let shared = SharedError2::from(BoxedError2::from_std(fmt::Error));
+        let other = shared.clone();
+
+        // `other` is alive, so the clone stays wrapped
+        let boxed = BoxedError2::from(shared).attach();
+        assert!(boxed.is::<fmt::Error>());
+        assert!(!boxed.is_root());
+
+        match boxed.downcast_ref::<fmt::Error>() {
+            Some(ErrorKind::Std { backtrace, .. }) => {
+                assert_eq!(backtrace, boxed.backtrace());
+                assert_eq!(locations(&boxed), 2);
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!(locations(&other), 1);
+
+        // `other` still shares the error, so it cannot be taken by value
+        let boxed = boxed.downcast::<fmt::Error>().map(|_| ()).unwrap_err();
+        assert!(boxed.is::<fmt::Error>());
+        drop(boxed);
+
+        let boxed = BoxedError2::from(other.clone()).attach();
+        drop(other);
+
+        // the other clones are gone, so the error unwraps with this backtrace
+        match boxed.downcast::<fmt::Error>() {
+            Ok(ErrorKind::Std { backtrace, .. }) => {
+                assert_eq!(backtrace.error_message().matches("\n    at ").count(), 2)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_downcast_err2_keeps_the_shared_backtrace() {
+        let origin = BoxedError2::from_root("timeout");
+        let remote = BoxedError2::from_backtrace(origin.backtrace().clone());
+
+        let shared = SharedError2::from(remote).attach();
+        let _other = shared.clone();
+        let boxed = BoxedError2::from(shared).attach();
+
+        match boxed.downcast_ref::<RemoteError>() {
+            Some(ErrorKind::Err2 { source }) => {
+                assert_eq!(locations(source), 2);
+                assert_eq!(locations(&boxed), 4);
+            }
+            _ => unreachable!(),
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use std::{
+use core::{
     error::Error,
     fmt::{self, Debug, Display, Formatter},
 };
@@ -51,4 +51,10 @@ impl<T: Error> Error2 for StdErr<T> {
     fn backtrace_mut(&mut self) -> &mut Backtrace {
         &mut self.backtrace
     }
+
+    #[cfg(feature = "tracing")]
+    #[inline]
+    fn event_type_name(&self) -> &str {
+        core::any::type_name::<T>()
+    }
 }
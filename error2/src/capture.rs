@@ -0,0 +1,260 @@
+#[cfg(feature = "std-backtrace")]
+use core::sync::atomic::AtomicBool;
+use core::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+
+#[cfg(feature = "threads")]
+use std::sync::OnceLock;
+
+const UNINIT: u8 = 0;
+
+/// `true` if the `release-off` feature compiles the recording out of this build.
+pub(crate) const COMPILED_OUT: bool = cfg!(all(feature = "release-off", not(debug_assertions)));
+
+static LEVEL: AtomicU8 = AtomicU8::new(UNINIT);
+
+const DEFAULT_MAX_LOCATIONS: usize = 64;
+
+static MAX_LOCATIONS: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_LOCATIONS);
+
+/// How much of an error's propagation is recorded in its [`Backtrace`](crate::Backtrace).
+///
+/// The level is read from the `ERROR2_BACKTRACE` environment variable the first
+/// time it is needed, unless it was set with [`set_capture_level`]:
+///
+/// | `ERROR2_BACKTRACE` | Level                        |
+/// |--------------------|------------------------------|
+/// | `0` or `off`       | [`Off`](CaptureLevel::Off)   |
+/// | `origin`           | [`Origin`](CaptureLevel::Origin) |
+/// | unset or other     | [`Full`](CaptureLevel::Full) |
+///
+/// Without the `std` feature there is no environment, and the level is `Full`
+/// unless it was set with [`set_capture_level`].
+///
+/// With the `release-off` feature, the level is always `Off` in builds without
+/// `debug_assertions`, and the recording is compiled out.
+///
+/// # Example
+///
+/// ```
+/// use error2::{capture::CaptureLevel, prelude::*};
+///
+/// #[derive(Debug, Error2)]
+/// #[error2(display("invalid id"))]
+/// struct InvalidId {
+///     backtrace: Backtrace,
+/// }
+///
+/// #[derive(Debug, Error2)]
+/// #[error2(display("request failed"))]
+/// struct RequestError {
+///     source: InvalidId,
+/// }
+///
+/// error2::capture::set_capture_level(CaptureLevel::Origin);
+///
+/// let err = Err::<(), _>(InvalidId2.build())
+///     .context(RequestError2)
+///     .attach()
+///     .unwrap_err();
+///
+/// let msg = err.backtrace().error_message();
+/// assert!(msg.contains("InvalidId: invalid id"));
+/// assert!(!msg.contains("RequestError"));
+/// assert_eq!(msg.matches("\n    at ").count(), 1);
+///
+/// error2::capture::set_capture_level(CaptureLevel::Off);
+///
+/// let err = InvalidId2.build();
+/// assert!(err.backtrace().error_message().is_empty());
+///
+/// let errors = error2::Errors::new(vec![InvalidId2.build()]).unwrap();
+/// assert!(errors.backtrace().error_message().is_empty());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum CaptureLevel {
+    /// Records nothing: no messages, locations, attachments, or the backtraces
+    /// of source and suppressed errors.
+    Off = 1,
+    /// Records only the message and location of the error where the chain
+    /// originates, skips `.context()` conversions and `.attach()` locations.
+    Origin = 2,
+    /// Records every message and location. This is the default.
+    Full = 3,
+}
+
+impl CaptureLevel {
+    #[cfg(feature = "std")]
+    fn from_env() -> Self {
+        match std::env::var("ERROR2_BACKTRACE").as_deref() {
+            Ok("0") | Ok("off") => CaptureLevel::Off,
+            Ok("origin") => CaptureLevel::Origin,
+            _ => CaptureLevel::Full,
+        }
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn from_env() -> Self {
+        CaptureLevel::Full
+    }
+}
+
+/// Sets the capture level, overriding the `ERROR2_BACKTRACE` environment variable.
+///
+/// Errors that are already propagating keep what they recorded so far.
+pub fn set_capture_level(level: CaptureLevel) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+/// Returns the current capture level.
+#[inline]
+pub fn capture_level() -> CaptureLevel {
+    if COMPILED_OUT {
+        return CaptureLevel::Off;
+    }
+
+    match LEVEL.load(Ordering::Relaxed) {
+        1 => CaptureLevel::Off,
+        2 => CaptureLevel::Origin,
+        3 => CaptureLevel::Full,
+        _ => {
+            let level = CaptureLevel::from_env();
+            // another thread may have set the level in the meantime
+            let _ =
+                LEVEL.compare_exchange(UNINIT, level as u8, Ordering::Relaxed, Ordering::Relaxed);
+            capture_level()
+        }
+    }
+}
+
+/// Sets the maximum number of locations recorded for each error in a backtrace.
+///
+/// Once an error has `max` locations, every further `.attach()` replaces its
+/// most recent location, and the replaced locations are counted in a
+/// `… N more locations elided` line. So the first `max - 1` locations and the
+/// most recent one are always kept. Values below 2 are treated as 2. The default is 64.
+///
+/// Consecutive identical locations, such as `.attach()` in a retry loop, are
+/// always collapsed into one line with a repeat count, regardless of this limit.
+///
+/// # Example
+///
+/// ```
+/// use error2::{Location, prelude::*};
+///
+/// error2::capture::set_max_locations(3);
+///
+/// let mut err = ViaRoot("timeout").build();
+/// for line in 1..=10 {
+///     err = err.attach_location(Location::new("src/pipeline.rs", line, 1));
+/// }
+///
+/// let msg = err.backtrace().error_message();
+/// # let msg = error2::strip_stamps(&msg);
+/// assert!(msg.contains("\n    at src/pipeline.rs:1:1"));
+/// assert!(msg.ends_with("\n    … 8 more locations elided\n    at src/pipeline.rs:10:1"));
+/// ```
+pub fn set_max_locations(max: usize) {
+    MAX_LOCATIONS.store(max.max(2), Ordering::Relaxed);
+}
+
+/// Returns the maximum number of locations recorded for each error in a backtrace.
+#[inline]
+pub fn max_locations() -> usize {
+    MAX_LOCATIONS.load(Ordering::Relaxed)
+}
+
+#[cfg(feature = "threads")]
+type TaskIdHook = fn() -> Option<u64>;
+
+#[cfg(feature = "threads")]
+static TASK_ID_HOOK: OnceLock<TaskIdHook> = OnceLock::new();
+
+/// Sets the function that identifies the current async task, recorded with
+/// each location alongside the thread.
+///
+/// The hook is called every time a location is recorded, return `None` when
+/// not running inside a task. Any runtime can be supported, for example with
+/// a task-local counter.
+///
+/// The hook can only be set once, so that reading it is lock-free. Later calls
+/// return the rejected hook as an error and keep the first one.
+///
+/// # Example
+///
+/// ```
+/// use std::cell::Cell;
+///
+/// use error2::{Location, prelude::*};
+/// use regex::Regex;
+///
+/// thread_local! {
+///     static TASK: Cell<Option<u64>> = const { Cell::new(None) };
+/// }
+///
+/// error2::capture::set_task_id_hook(|| TASK.get()).unwrap();
+/// assert!(error2::capture::set_task_id_hook(|| None).is_err());
+///
+/// let err = ViaRoot("timeout").build();
+/// TASK.set(Some(42));
+/// let err = err.attach_location(Location::new("src/handler.rs", 9, 1));
+///
+/// let msg = err.backtrace().error_message();
+/// // the elapsed time is shown before the thread with the `timestamps` feature
+/// let re = Regex::new(r"\n    at src/handler\.rs:9:1 (\(\+.+\) )?\[thread .+, task 42\]$").unwrap();
+/// assert!(re.is_match(msg.as_ref()));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "threads")))]
+#[cfg(feature = "threads")]
+pub fn set_task_id_hook(hook: fn() -> Option<u64>) -> Result<(), fn() -> Option<u64>> {
+    TASK_ID_HOOK.set(hook)
+}
+
+#[cfg(feature = "threads")]
+pub(crate) fn task_id() -> Option<u64> {
+    let hook = TASK_ID_HOOK.get()?;
+    hook()
+}
+
+#[cfg(feature = "std-backtrace")]
+static FORCE_STD_BACKTRACE: AtomicBool = AtomicBool::new(false);
+
+/// Captures the call stack where an error chain originates even if the
+/// `RUST_LIB_BACKTRACE` and `RUST_BACKTRACE` environment variables disable it.
+///
+/// By default the stack is captured with [`std::backtrace::Backtrace::capture`],
+/// so only when the environment enables it. See
+/// [`Backtrace::stack_trace`](crate::Backtrace::stack_trace).
+///
+/// # Example
+///
+/// ```
+/// use error2::prelude::*;
+///
+/// error2::capture::set_force_std_backtrace(true);
+///
+/// let err = ViaRoot("disk full").build();
+/// assert!(err.backtrace().stack_trace().is_some());
+///
+/// let rendered = err.backtrace().render().extended().to_string();
+/// assert!(rendered.contains("\n    stack backtrace:\n"));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std-backtrace")))]
+#[cfg(feature = "std-backtrace")]
+pub fn set_force_std_backtrace(force: bool) {
+    FORCE_STD_BACKTRACE.store(force, Ordering::Relaxed);
+}
+
+/// Captures the call stack, or returns `None` if capturing is disabled.
+#[cfg(feature = "std-backtrace")]
+pub(crate) fn std_backtrace() -> Option<std::backtrace::Backtrace> {
+    use std::backtrace::{Backtrace, BacktraceStatus};
+
+    let backtrace = if FORCE_STD_BACKTRACE.load(Ordering::Relaxed) {
+        Backtrace::force_capture()
+    } else {
+        Backtrace::capture()
+    };
+
+    (backtrace.status() == BacktraceStatus::Captured).then_some(backtrace)
+}
@@ -0,0 +1,107 @@
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+#[cfg(feature = "stream")]
+mod stream;
+
+use crate::{Error2, Errors, Location};
+use alloc::vec::Vec;
+
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+#[cfg(feature = "stream")]
+pub use self::stream::{CollectAllFuture, CollectAllStream, TryCollectAllFuture};
+
+/// Collect every error of an iterator of results, instead of stopping at the first.
+///
+/// Unlike `Iterator::collect::<Result<_, _>>()`, which short-circuits, these methods
+/// consume the whole iterator and gather all errors into an [`Errors`] collection.
+/// See [`CollectAllStream`] for streams.
+///
+/// # Example
+///
+/// ```
+/// use error2::{Errors, prelude::*};
+///
+/// #[derive(Debug, Error2)]
+/// #[error2(display("invalid number: {input}"))]
+/// struct InvalidNumber {
+///     input: String,
+///     source: std::num::ParseIntError,
+///     backtrace: Backtrace,
+/// }
+///
+/// fn parse(input: &str) -> Result<i32, InvalidNumber> {
+///     input.parse().context(InvalidNumber2 { input })
+/// }
+///
+/// let all: Result<Vec<i32>, Errors<InvalidNumber>> =
+///     ["1", "x", "3", "y"].into_iter().map(parse).try_collect_all();
+///
+/// let errors = all.unwrap_err();
+/// assert_eq!(errors.len(), 2);
+///
+/// let (valid, errors): (Vec<i32>, _) = ["1", "x", "3"].into_iter().map(parse).collect_all();
+///
+/// assert_eq!(valid, [1, 3]);
+/// assert_eq!(errors.map(|e| e.len()), Some(1));
+/// ```
+pub trait CollectAll<T, E>: Iterator<Item = Result<T, E>> + Sized
+where
+    E: Error2,
+{
+    /// Collects all `Ok` values, or all errors if there was at least one.
+    ///
+    /// Automatically captures the caller's location for backtrace.
+    #[track_caller]
+    #[inline]
+    fn try_collect_all<C: FromIterator<T>>(self) -> Result<C, Errors<E>> {
+        self.try_collect_all_with_location(Location::tracked())
+    }
+
+    /// Collects with explicit location.
+    fn try_collect_all_with_location<C: FromIterator<T>>(
+        self,
+        location: Location,
+    ) -> Result<C, Errors<E>> {
+        let (collection, errors) = self.collect_all_with_location(location);
+
+        match errors {
+            None => Ok(collection),
+            Some(errors) => Err(errors),
+        }
+    }
+
+    /// Collects all `Ok` values, along with all errors if there was at least one.
+    ///
+    /// Automatically captures the caller's location for backtrace.
+    #[track_caller]
+    #[inline]
+    fn collect_all<C: FromIterator<T>>(self) -> (C, Option<Errors<E>>) {
+        self.collect_all_with_location(Location::tracked())
+    }
+
+    /// Collects with explicit location.
+    fn collect_all_with_location<C: FromIterator<T>>(
+        self,
+        location: Location,
+    ) -> (C, Option<Errors<E>>) {
+        let mut errors = Vec::new();
+
+        let collection = self
+            .filter_map(|result| match result {
+                Ok(t) => Some(t),
+                Err(e) => {
+                    errors.push(e);
+                    None
+                }
+            })
+            .collect::<C>();
+
+        (collection, Errors::new_with_location(errors, location))
+    }
+}
+
+impl<T, E, I> CollectAll<T, E> for I
+where
+    E: Error2,
+    I: Iterator<Item = Result<T, E>>,
+{
+}
@@ -0,0 +1,180 @@
+use alloc::vec::Vec;
+use core::{
+    mem,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_core::Stream;
+use pin_project_lite::pin_project;
+
+use crate::{Error2, Errors, Location};
+
+/// Collect every error of a stream of results, instead of stopping at the first.
+///
+/// The stream counterpart of [`CollectAll`](crate::CollectAll). The returned
+/// futures poll the stream to its end, and the values are collected into any
+/// `Default + Extend` collection, such as `Vec`.
+///
+/// # Example
+///
+/// ```
+/// use std::{
+///     pin::{Pin, pin},
+///     task::{Context, Poll, Waker},
+/// };
+///
+/// use error2::{CollectAllStream, Errors, prelude::*};
+/// use futures_core::Stream;
+///
+/// #[derive(Debug, Error2)]
+/// #[error2(display("invalid number: {input}"))]
+/// struct InvalidNumber {
+///     input: String,
+///     source: std::num::ParseIntError,
+///     backtrace: Backtrace,
+/// }
+///
+/// /// A stream over the parsed inputs.
+/// struct Parsed(std::vec::IntoIter<&'static str>);
+///
+/// impl Stream for Parsed {
+///     type Item = Result<i32, InvalidNumber>;
+///
+///     fn poll_next(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+///         let item = self.0.next().map(|input| input.parse().context(InvalidNumber2 { input }));
+///         Poll::Ready(item)
+///     }
+/// }
+///
+/// let stream = Parsed(vec!["1", "x", "3", "y"].into_iter());
+/// let future = pin!(stream.try_collect_all::<Vec<i32>>());
+///
+/// let mut cx = Context::from_waker(Waker::noop());
+/// let Poll::Ready(Err(errors)) = future.poll(&mut cx) else {
+///     unreachable!()
+/// };
+/// assert_eq!(errors.len(), 2);
+/// ```
+pub trait CollectAllStream<T, E>: Stream<Item = Result<T, E>> + Sized
+where
+    E: Error2,
+{
+    /// Collects all `Ok` values, or all errors if there was at least one.
+    ///
+    /// Automatically captures the caller's location for backtrace.
+    #[track_caller]
+    #[inline]
+    fn try_collect_all<C: Default + Extend<T>>(self) -> TryCollectAllFuture<Self, C, E> {
+        self.try_collect_all_with_location(Location::tracked())
+    }
+
+    /// Collects with explicit location.
+    #[inline]
+    fn try_collect_all_with_location<C: Default + Extend<T>>(
+        self,
+        location: Location,
+    ) -> TryCollectAllFuture<Self, C, E> {
+        TryCollectAllFuture {
+            inner: self.collect_all_with_location(location),
+        }
+    }
+
+    /// Collects all `Ok` values, along with all errors if there was at least one.
+    ///
+    /// Automatically captures the caller's location for backtrace.
+    #[track_caller]
+    #[inline]
+    fn collect_all<C: Default + Extend<T>>(self) -> CollectAllFuture<Self, C, E> {
+        self.collect_all_with_location(Location::tracked())
+    }
+
+    /// Collects with explicit location.
+    #[inline]
+    fn collect_all_with_location<C: Default + Extend<T>>(
+        self,
+        location: Location,
+    ) -> CollectAllFuture<Self, C, E> {
+        CollectAllFuture {
+            inner: self,
+            collection: C::default(),
+            errors: Vec::new(),
+            location,
+        }
+    }
+}
+
+impl<T, E, S> CollectAllStream<T, E> for S
+where
+    E: Error2,
+    S: Stream<Item = Result<T, E>>,
+{
+}
+
+pin_project! {
+    /// Future returned by [`CollectAllStream::collect_all`].
+    #[derive(Debug)]
+    #[must_use = "futures do nothing unless you `.await` or poll them"]
+    pub struct CollectAllFuture<S, C, E> {
+        #[pin]
+        inner: S,
+        collection: C,
+        errors: Vec<E>,
+        location: Location,
+    }
+}
+
+impl<T, E, S, C> Future for CollectAllFuture<S, C, E>
+where
+    E: Error2,
+    S: Stream<Item = Result<T, E>>,
+    C: Default + Extend<T>,
+{
+    type Output = (C, Option<Errors<E>>);
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut this = self.project();
+
+        loop {
+            match this.inner.as_mut().poll_next(cx) {
+                Poll::Ready(Some(Ok(t))) => this.collection.extend(Some(t)),
+                Poll::Ready(Some(Err(e))) => this.errors.push(e),
+                Poll::Ready(None) => {
+                    let collection = mem::take(this.collection);
+                    let errors = Errors::new_with_location(mem::take(this.errors), *this.location);
+
+                    return Poll::Ready((collection, errors));
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+pin_project! {
+    /// Future returned by [`CollectAllStream::try_collect_all`].
+    #[derive(Debug)]
+    #[must_use = "futures do nothing unless you `.await` or poll them"]
+    pub struct TryCollectAllFuture<S, C, E> {
+        #[pin]
+        inner: CollectAllFuture<S, C, E>,
+    }
+}
+
+impl<T, E, S, C> Future for TryCollectAllFuture<S, C, E>
+where
+    E: Error2,
+    S: Stream<Item = Result<T, E>>,
+    C: Default + Extend<T>,
+{
+    type Output = Result<C, Errors<E>>;
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project().inner.poll(cx) {
+            Poll::Ready((collection, None)) => Poll::Ready(Ok(collection)),
+            Poll::Ready((_, Some(errors))) => Poll::Ready(Err(errors)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
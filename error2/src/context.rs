@@ -1,4 +1,4 @@
-use std::error::Error;
+use core::error::Error;
 
 use crate::{Error2, Location, transform::SourceToTarget};
 
@@ -127,7 +127,7 @@ where
     #[inline]
     #[track_caller]
     fn context(self, context: C) -> Result<T, Target> {
-        self.context_and_location(context, Location::caller())
+        self.context_and_location(context, Location::tracked())
     }
 
     /// Converts with explicit location (rarely needed).
@@ -142,7 +142,7 @@ where
     where
         F: FnOnce() -> C,
     {
-        self.with_context_and_location(f, Location::caller())
+        self.with_context_and_location(f, Location::tracked())
     }
 
     /// Lazy conversion with explicit location.
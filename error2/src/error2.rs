@@ -1,4 +1,5 @@
-use std::{convert::Infallible, error::Error};
+use alloc::{boxed::Box, vec::Vec};
+use core::{convert::Infallible, error::Error, fmt::Display};
 
 use crate::Backtrace;
 
@@ -41,6 +42,62 @@ use crate::Backtrace;
 /// ```
 ///
 /// See [`Backtrace`] for details on accessing error information.
+///
+/// # Lazy Display Messages
+///
+/// The display message of an error is rendered when it is recorded in the
+/// backtrace. Errors derived with `#[error2(lazy)]` defer it until
+/// [`backtrace`](Error2::backtrace) is first called, so errors that are matched
+/// and discarded never format it:
+///
+/// ```
+/// use std::{
+///     fmt,
+///     sync::atomic::{AtomicUsize, Ordering},
+/// };
+///
+/// use error2::prelude::*;
+///
+/// static RENDERED: AtomicUsize = AtomicUsize::new(0);
+///
+/// #[derive(Debug, Error2)]
+/// #[error2(lazy)]
+/// struct ParseError {
+///     backtrace: Backtrace,
+/// }
+///
+/// impl fmt::Display for ParseError {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         RENDERED.fetch_add(1, Ordering::Relaxed);
+///         write!(f, "parse error")
+///     }
+/// }
+///
+/// let err = ParseError2.build();
+/// assert_eq!(RENDERED.load(Ordering::Relaxed), 0);
+///
+/// assert!(err.backtrace().error_message().contains("ParseError: parse error"));
+/// assert_eq!(RENDERED.load(Ordering::Relaxed), 1);
+/// ```
+///
+/// The deferred messages are rendered from the error itself, so a backtrace
+/// that is moved out of a lazy error before being accessed, for example by
+/// destructuring the error, only shows the type names of those errors. Only
+/// opt into `lazy` for errors whose backtrace is read through the error.
+/// Other errors keep their display message wherever the backtrace is moved:
+///
+/// ```
+/// use error2::prelude::*;
+///
+/// #[derive(Debug, Error2)]
+/// #[error2(display("parse error"))]
+/// struct ParseError {
+///     backtrace: Backtrace,
+/// }
+///
+/// let ParseError { backtrace } = ParseError2.build();
+/// assert!(backtrace.error_message().contains("ParseError: parse error"));
+/// ```
 pub trait Error2: Error {
     /// Returns a reference to the error's backtrace.
     ///
@@ -51,6 +108,71 @@ pub trait Error2: Error {
     ///
     /// Used internally by the library to record error propagation locations.
     fn backtrace_mut(&mut self) -> &mut Backtrace;
+
+    /// Returns this error's own user-facing message, if any.
+    ///
+    /// Generated by `#[error2(public = "...")]`. It is recorded in the backtrace
+    /// when the error is created, so that outer errors can find it with
+    /// [`Backtrace::public_message`].
+    #[inline]
+    fn public_message(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Returns the level of the `tracing` events emitted for this error, if any.
+    ///
+    /// Generated by `#[error2(level = "...")]`. `None` falls back to the
+    /// [default level](crate::trace::set_default_level).
+    #[cfg_attr(docsrs, doc(cfg(feature = "tracing")))]
+    #[cfg(feature = "tracing")]
+    #[inline]
+    fn event_level(&self) -> Option<crate::trace::Level> {
+        None
+    }
+
+    /// Returns the type name reported by `tracing` events, the one of the
+    /// wrapped error for type-erased errors such as [`BoxedError2`](crate::BoxedError2).
+    #[doc(hidden)]
+    #[cfg(feature = "tracing")]
+    #[inline]
+    fn event_type_name(&self) -> &str {
+        core::any::type_name::<Self>()
+    }
+
+    /// Returns the nearest user-facing message along the error chain, or the
+    /// configured fallback message.
+    ///
+    /// Unlike `Display`, this never exposes internal details such as file paths
+    /// or host names. See [`set_public_fallback`](crate::render::set_public_fallback)
+    /// to configure the fallback message.
+    #[inline]
+    fn user_message(&self) -> &str {
+        self.backtrace()
+            .public_message()
+            .unwrap_or_else(|| crate::render::public_fallback())
+    }
+
+    /// Returns the number of display messages this error and its `Error2`
+    /// sources record in the backtrace, if all of them can be displayed later
+    /// with [`collect_displays`](Self::collect_displays).
+    ///
+    /// Generated by `#[derive(Error2)]` for `#[error2(lazy)]` types, so that the
+    /// display message is only rendered when the backtrace is accessed. Errors
+    /// with redacted fields return `None`, their message is rendered when they
+    /// are created, under the [reveal policy](crate::render::set_reveal) of that time.
+    #[doc(hidden)]
+    #[inline]
+    fn display_depth(&self) -> Option<usize> {
+        None
+    }
+
+    /// Collects this error and its `Error2` sources, from the innermost to the
+    /// outermost, for resolving their display messages in the backtrace.
+    #[doc(hidden)]
+    #[inline]
+    fn collect_displays<'a>(&'a self, displays: &mut Vec<&'a dyn Display>) {
+        let _ = displays;
+    }
 }
 
 impl Error2 for Infallible {
@@ -73,4 +195,31 @@ impl<T: Error2> Error2 for Box<T> {
     fn backtrace_mut(&mut self) -> &mut Backtrace {
         self.as_mut().backtrace_mut()
     }
+
+    #[inline]
+    fn public_message(&self) -> Option<&'static str> {
+        self.as_ref().public_message()
+    }
+
+    #[cfg(feature = "tracing")]
+    #[inline]
+    fn event_level(&self) -> Option<crate::trace::Level> {
+        self.as_ref().event_level()
+    }
+
+    #[cfg(feature = "tracing")]
+    #[inline]
+    fn event_type_name(&self) -> &str {
+        self.as_ref().event_type_name()
+    }
+
+    #[inline]
+    fn display_depth(&self) -> Option<usize> {
+        self.as_ref().display_depth()
+    }
+
+    #[inline]
+    fn collect_displays<'a>(&'a self, displays: &mut Vec<&'a dyn Display>) {
+        self.as_ref().collect_displays(displays)
+    }
 }
@@ -0,0 +1,206 @@
+use alloc::{vec, vec::Vec};
+use core::{
+    error::Error,
+    fmt::{self, Debug, Display, Formatter},
+    slice,
+};
+
+use crate::{Backtrace, BoxedError2, Error2, Location};
+
+/// A collection of independent errors, reported together.
+///
+/// `Errors` is useful for validation and batch jobs, where dozens of errors can
+/// occur and all of them should be reported. Each inner error keeps its own
+/// backtrace, and the collection renders them as an indented tree below its own
+/// location. A collection always holds at least one error.
+///
+/// Use [`CollectAll`](crate::CollectAll) to gather the errors of an iterator of results.
+///
+/// # Example
+///
+/// ```
+/// use error2::{Errors, prelude::*};
+/// use regex::Regex;
+///
+/// #[derive(Debug, Error2)]
+/// #[error2(display("invalid field `{name}`"))]
+/// struct InvalidField {
+///     name: String,
+///     backtrace: Backtrace,
+/// }
+///
+/// let errors = Errors::new(vec![
+///     InvalidField2 { name: "port" }.build(),
+///     InvalidField2 { name: "host" }.build(),
+/// ])
+/// .unwrap();
+///
+/// assert_eq!(errors.len(), 2);
+/// assert_eq!(errors.to_string(), "2 errors occurred");
+///
+/// let msg = errors.backtrace().error_message();
+/// # let msg = error2::strip_stamps(&msg);
+///
+/// // Errors<InvalidField>: 2 errors occurred
+/// //     at /path/to/file.rs:35:14
+/// //     [0] InvalidField: invalid field `port`
+/// //             at /path/to/file.rs:33:37
+/// //     [1] InvalidField: invalid field `host`
+/// //             at /path/to/file.rs:34:37
+///
+/// let re = Regex::new(concat!(
+///     r"(?s)^.+Errors<.+InvalidField>: 2 errors occurred",
+///     r"\n    at .+\.rs:\d+:\d+",
+///     r"\n    \[0\] .+InvalidField: invalid field `port`",
+///     r"\n            at .+\.rs:\d+:\d+",
+///     r"\n    \[1\] .+InvalidField: invalid field `host`",
+///     r"\n            at .+\.rs:\d+:\d+$",
+/// ))
+/// .unwrap();
+/// assert!(re.is_match(msg.as_ref()));
+/// ```
+pub struct Errors<E> {
+    errors: Vec<E>,
+    backtrace: Backtrace,
+}
+
+/// A collection of type-erased errors.
+pub type BoxedErrors2 = Errors<BoxedError2>;
+
+impl<E: Error2> Errors<E> {
+    /// Creates a collection from the given errors, or returns `None` if there
+    /// are none.
+    ///
+    /// Automatically captures the caller's location for backtrace.
+    #[track_caller]
+    #[inline]
+    pub fn new(errors: Vec<E>) -> Option<Self> {
+        Self::new_with_location(errors, Location::tracked())
+    }
+
+    /// Creates a collection with explicit location.
+    pub fn new_with_location(errors: Vec<E>, location: Location) -> Option<Self> {
+        if errors.is_empty() {
+            return None;
+        }
+
+        let children = crate::source_backtraces(&errors);
+
+        let mut error = Self {
+            errors,
+            backtrace: Backtrace::new(),
+        };
+
+        crate::push_error_with_sources(&mut error, children, location);
+
+        Some(error)
+    }
+    /// Consumes the collection, returning the errors and the backtrace of the
+    /// collection, with the locations and attachments it recorded.
+    pub fn into_parts(self) -> (Vec<E>, Backtrace) {
+        // resolves the pending display message before `self` is dropped
+        self.backtrace();
+
+        (self.errors, self.backtrace)
+    }
+}
+
+impl<E> Errors<E> {
+    /// Returns the number of errors.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    /// Returns `true` if there are no errors.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Returns an iterator over the errors.
+    #[inline]
+    pub fn iter(&self) -> slice::Iter<'_, E> {
+        self.errors.iter()
+    }
+
+    /// Returns the errors as a slice.
+    #[inline]
+    pub fn as_slice(&self) -> &[E] {
+        &self.errors
+    }
+
+    /// Consumes the collection, returning the errors.
+    #[inline]
+    pub fn into_vec(self) -> Vec<E> {
+        self.errors
+    }
+}
+
+impl<E> From<Errors<E>> for Vec<E> {
+    #[inline]
+    fn from(errors: Errors<E>) -> Self {
+        errors.errors
+    }
+}
+
+impl<E> IntoIterator for Errors<E> {
+    type IntoIter = vec::IntoIter<E>;
+    type Item = E;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.errors.into_iter()
+    }
+}
+
+impl<'a, E> IntoIterator for &'a Errors<E> {
+    type IntoIter = slice::Iter<'a, E>;
+    type Item = &'a E;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.errors.iter()
+    }
+}
+
+impl<E> Display for Errors<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.errors.len() {
+            1 => write!(f, "1 error occurred"),
+            n => write!(f, "{} errors occurred", n),
+        }
+    }
+}
+
+impl<E: Debug> Debug for Errors<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Errors")
+            .field("errors", &self.errors)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<E: Debug> Error for Errors<E> {}
+
+impl<E: Debug> Error2 for Errors<E> {
+    #[inline]
+    fn backtrace(&self) -> &Backtrace {
+        crate::resolve_displays(self, &self.backtrace)
+    }
+
+    #[inline]
+    fn backtrace_mut(&mut self) -> &mut Backtrace {
+        &mut self.backtrace
+    }
+
+    #[inline]
+    fn display_depth(&self) -> Option<usize> {
+        Some(1)
+    }
+
+    #[inline]
+    fn collect_displays<'a>(&'a self, displays: &mut Vec<&'a dyn Display>) {
+        displays.push(self);
+    }
+}
@@ -1,54 +1,306 @@
-use crate::{Backtrace, BakctraceEntry, Location};
+#[cfg(feature = "std")]
+use crate::render::{SourceCache, SourceSnippets};
+use crate::{
+    Backtrace, BakctraceEntry,
+    render::{FilterAction, FrameFilter, PathRemap},
+};
+use alloc::{borrow::Cow, boxed::Box, format, string::String, string::ToString, vec::Vec};
 
-fn flush_msg(mut msg: String, locations: &mut Vec<&Location>, stack: &mut Vec<Box<str>>) {
-    if msg.is_empty() {
-        debug_assert!(locations.is_empty());
+/// How locations are shown by [`extract_error_message_with`].
+pub(crate) struct Options<'a> {
+    pub(crate) path_remap: Option<&'a PathRemap>,
+    pub(crate) frame_filter: Option<&'a FrameFilter>,
+    /// The snippets to show and the sources read for them so far.
+    #[cfg(feature = "std")]
+    pub(crate) source_snippets: Option<(&'a SourceSnippets, &'a SourceCache)>,
+    /// Shows the captured call stacks.
+    #[cfg(feature = "std-backtrace")]
+    pub(crate) extended: bool,
+}
+
+fn push_indented(msg: &mut String, label: &str, child: &Backtrace, options: &Options<'_>) {
+    let child = extract_error_message_with(child, options);
+    let mut lines = child.lines();
+
+    let Some(first) = lines.next() else {
         return;
+    };
+
+    msg.push_str("\n    ");
+    msg.push_str(label);
+    msg.push(' ');
+    msg.push_str(first);
+
+    for line in lines {
+        msg.push_str("\n        ");
+        msg.push_str(line);
     }
+}
 
-    for location in locations.iter() {
-        msg.push_str("\n    at ");
-        msg.push_str(&location.to_string());
+fn flush_msg(msg: String, stack: &mut Vec<Box<str>>) {
+    if msg.is_empty() {
+        return;
     }
 
     stack.push(msg.into());
-    locations.clear();
 }
 
-fn extract_error_stack(backtrace: &Backtrace) -> Box<[Box<str>]> {
+/// Lines held back until the next entry, so that `(repeated N times)` can
+/// still be appended to the last location.
+#[derive(Default)]
+struct Pending {
+    /// The snippet of the last shown location.
+    #[cfg(feature = "std")]
+    snippet: Option<(String, u32, u32)>,
+    /// The rendered spans active at the origin, shown below its location.
+    #[cfg(feature = "tracing")]
+    spans: Option<String>,
+    /// The rendered call stack captured at the origin, shown below its location.
+    #[cfg(feature = "std-backtrace")]
+    stack: Option<String>,
+    /// The number of locations collapsed since the last line.
+    collapsed: u64,
+}
+
+impl Pending {
+    fn flush(&mut self, msg: &mut String, options: &Options<'_>) {
+        #[cfg(feature = "std")]
+        if let (Some((path, line, column)), Some((source_snippets, cache))) =
+            (self.snippet.take(), options.source_snippets)
+        {
+            source_snippets.write(msg, cache, &path, line, column);
+        }
+
+        #[cfg(feature = "tracing")]
+        if let Some(spans) = self.spans.take() {
+            msg.push_str(&spans);
+        }
+
+        #[cfg(feature = "std-backtrace")]
+        if let Some(stack) = self.stack.take() {
+            msg.push_str("\n    stack backtrace:");
+
+            for line in stack.lines() {
+                msg.push_str("\n    ");
+                msg.push_str(line);
+            }
+        }
+
+        #[cfg(not(feature = "std"))]
+        let _ = options;
+
+        match self.collapsed {
+            0 => return,
+            1 => msg.push_str("\n    … 1 filtered location"),
+            n => msg.push_str(&format!("\n    … {} filtered locations", n)),
+        }
+
+        self.collapsed = 0;
+    }
+}
+
+/// Formats the time since the origin as `+12.3ms`.
+#[cfg(feature = "timestamps")]
+pub(crate) fn format_delta(delta: core::time::Duration) -> String {
+    let nanos = delta.as_nanos();
+
+    match nanos {
+        0..1_000 => format!("+{}ns", nanos),
+        1_000..1_000_000 => format!("+{:.1}µs", nanos as f64 / 1e3),
+        1_000_000..1_000_000_000 => format!("+{:.1}ms", nanos as f64 / 1e6),
+        _ => format!("+{:.1}s", delta.as_secs_f64()),
+    }
+}
+
+/// Removes the time deltas and threads of the `timestamps` and `threads`
+/// features from an error message, so that tests and examples compare the
+/// same output with and without them.
+#[doc(hidden)]
+pub fn strip_stamps(msg: &str) -> String {
+    let mut stripped = String::with_capacity(msg.len());
+    let mut rest = msg;
+
+    loop {
+        let stamp = [(" (+", ')'), (" [thread ", ']')]
+            .into_iter()
+            .filter_map(|(open, close)| {
+                let start = rest.find(open)?;
+                let len = rest[start + open.len()..].find(close)?;
+                Some((start, start + open.len() + len + 1))
+            })
+            .min();
+
+        match stamp {
+            Some((start, end)) => {
+                stripped.push_str(&rest[..start]);
+                rest = &rest[end..];
+            }
+            None => {
+                stripped.push_str(rest);
+                return stripped;
+            }
+        }
+    }
+}
+
+fn extract_error_stack(backtrace: &Backtrace, options: &Options<'_>) -> Box<[Box<str>]> {
     let (head, entries) = backtrace.head_and_entries();
 
-    let mut stack: Vec<Box<str>> = Vec::with_capacity(entries.len());
+    let mut stack: Vec<Box<str>> = Vec::new();
 
     if let Some(head) = head {
-        stack.push(format!("{}: {}", head.type_name(), head.display()).into());
+        stack.push(head.to_string().into());
     }
 
     {
         let mut msg = String::new();
-        let mut locations = Vec::<&Location>::new();
+        let mut child_index = 0usize;
+        // the action taken on the last location
+        let mut filtered: Option<FilterAction> = None;
+        let mut pending = Pending::default();
+        // the origin itself is not annotated
+        #[cfg(feature = "timestamps")]
+        let origin = backtrace.origin_stamp();
+        #[cfg(feature = "timestamps")]
+        let mut at_origin = true;
+        // consecutive locations on the same thread are only annotated once
+        #[cfg(feature = "threads")]
+        let mut last_hop = None;
+        // the spans and call stack of the current frame, until its first location
+        #[cfg(feature = "tracing")]
+        let mut origin_spans = None;
+        #[cfg(feature = "std-backtrace")]
+        let mut origin_stack = None;
 
         for entry in entries {
+            if !matches!(
+                entry,
+                BakctraceEntry::Locations(_) | BakctraceEntry::Repeated(_)
+            ) {
+                pending.flush(&mut msg, options);
+            }
+
             match entry {
                 BakctraceEntry::Message(message) => {
-                    flush_msg(msg, &mut locations, &mut stack);
+                    flush_msg(msg, &mut stack);
 
-                    msg = format!("{}: {}", message.type_name(), message.display());
+                    msg = message.to_string();
+                    child_index = 0;
+                    filtered = None;
+
+                    #[cfg(feature = "tracing")]
+                    {
+                        origin_spans = message.spans();
+                    }
+
+                    #[cfg(feature = "std-backtrace")]
+                    {
+                        origin_stack = message.stack().filter(|_| options.extended);
+                    }
                 }
+                // locations and markers before the first message have no frame to
+                // belong to, only a backtrace received from a peer can record them
+                BakctraceEntry::Locations(_)
+                | BakctraceEntry::Repeated(_)
+                | BakctraceEntry::Elided(_)
+                    if msg.is_empty() => {}
                 BakctraceEntry::Locations(dl) => {
-                    let [first, second] = dl.inner();
+                    for location in dl.iter() {
+                        let (file, line, column) = location.key();
+                        let rendered = match options.path_remap {
+                            Some(path_remap) => path_remap.apply(file),
+                            None => Cow::Borrowed(file),
+                        };
+
+                        filtered = options
+                            .frame_filter
+                            .and_then(|frame_filter| frame_filter.action(file, &rendered));
+
+                        #[cfg(feature = "timestamps")]
+                        let is_origin = core::mem::replace(&mut at_origin, false);
 
-                    debug_assert!(!first.is_uninit());
-                    locations.push(first);
+                        match filtered {
+                            Some(FilterAction::Hide) => {}
+                            Some(FilterAction::Collapse) => pending.collapsed += 1,
+                            None => {
+                                pending.flush(&mut msg, options);
+                                msg.push_str(&format!("\n    at {}:{}:{}", rendered, line, column));
 
-                    if !second.is_uninit() {
-                        locations.push(second);
+                                #[cfg(feature = "timestamps")]
+                                if let Some(delta) =
+                                    origin.and_then(|origin| location.stamp().since(origin))
+                                    && !is_origin
+                                {
+                                    msg.push_str(" (");
+                                    msg.push_str(&format_delta(delta));
+                                    msg.push(')');
+                                }
+
+                                #[cfg(feature = "threads")]
+                                if let Some(hop) = location.stamp().hop()
+                                    && last_hop.replace(hop) != Some(hop)
+                                {
+                                    msg.push_str(&format!(" [{}]", hop));
+                                }
+
+                                #[cfg(feature = "std")]
+                                if options.source_snippets.is_some() {
+                                    pending.snippet = Some((rendered.into_owned(), line, column));
+                                }
+                            }
+                        }
+
+                        // shown after the first location, even if it is filtered
+                        #[cfg(feature = "tracing")]
+                        if let Some(spans) = origin_spans.take() {
+                            let mut rendered = String::new();
+                            crate::trace::write_spans(&mut rendered, spans);
+                            pending.spans = Some(rendered);
+                        }
+
+                        #[cfg(feature = "std-backtrace")]
+                        if let Some(stack) = origin_stack.take() {
+                            pending.stack = Some(stack.to_string());
+                        }
+                    }
+                }
+                BakctraceEntry::Attachment(attachment) => {
+                    // attachments made before the first message have no frame to belong to
+                    if let Some(display) = attachment.display()
+                        && !msg.is_empty()
+                    {
+                        msg.push_str("\n    note: ");
+                        msg.push_str(display);
+                    }
+                }
+                BakctraceEntry::Child(child) => {
+                    if !msg.is_empty() {
+                        push_indented(&mut msg, &format!("[{}]", child_index), child, options);
+                        child_index += 1;
+                    }
+                }
+                BakctraceEntry::Suppressed(suppressed) => {
+                    if !msg.is_empty() {
+                        push_indented(&mut msg, "suppressed:", suppressed, options);
                     }
                 }
+                BakctraceEntry::Repeated(count) => match filtered {
+                    Some(FilterAction::Hide) => {}
+                    Some(FilterAction::Collapse) => pending.collapsed += u64::from(*count),
+                    None => msg.push_str(&format!(" (repeated {} times)", u64::from(*count) + 1)),
+                },
+                BakctraceEntry::Elided(count) => {
+                    msg.push_str(&format!(
+                        "\n    … {} more location{} elided",
+                        count,
+                        if *count == 1 { "" } else { "s" }
+                    ));
+                }
             }
         }
 
-        flush_msg(msg, &mut locations, &mut stack);
+        pending.flush(&mut msg, options);
+        flush_msg(msg, &mut stack);
     }
 
     stack.reverse();
@@ -57,7 +309,23 @@ fn extract_error_stack(backtrace: &Backtrace) -> Box<[Box<str>]> {
 }
 
 pub(crate) fn extract_error_message(backtrace: &Backtrace) -> Box<str> {
-    let stack = extract_error_stack(backtrace);
+    let path_remap = crate::render::global_path_remap();
+    let frame_filter = crate::render::global_frame_filter();
+
+    let options = Options {
+        path_remap: path_remap.as_deref(),
+        frame_filter: frame_filter.as_deref(),
+        #[cfg(feature = "std")]
+        source_snippets: None,
+        #[cfg(feature = "std-backtrace")]
+        extended: false,
+    };
+
+    extract_error_message_with(backtrace, &options)
+}
+
+pub(crate) fn extract_error_message_with(backtrace: &Backtrace, options: &Options<'_>) -> Box<str> {
+    let stack = extract_error_stack(backtrace, options);
 
     if stack.is_empty() {
         Box::from("")
@@ -74,3 +342,28 @@ pub(crate) fn extract_error_message(backtrace: &Backtrace) -> Box<str> {
         buf.into()
     }
 }
+
+pub(crate) fn extract_public_message(backtrace: &Backtrace) -> Box<str> {
+    let mut buf = String::new();
+    let mut last: Option<&str> = None;
+
+    for public in backtrace.public_messages() {
+        // an `Error2` wrapper such as `BoxedError2` repeats the public message of the error it wraps
+        if last == Some(public) {
+            continue;
+        }
+
+        if last.is_some() {
+            buf.push('\n');
+        }
+
+        buf.push_str(public);
+        last = Some(public);
+    }
+
+    if last.is_none() {
+        buf.push_str(crate::render::public_fallback());
+    }
+
+    buf.into()
+}
@@ -10,6 +10,27 @@
 //! - **Derive Macro** - `#[derive(Error2)]` for easy error type creation
 //! - **Type Conversion** - `Result<T, E1> -> Result<T, E2>`, `Option<T> -> Result<T, E>` with `.context()`
 //! - **Type Erasure** - `BoxedError2` for anyhow-like ergonomics
+//! - **Shared Errors** - `SharedError2` to clone errors for caches and broadcast channels
+//! - **Remote Errors** - `RemoteError` to keep propagating errors received from another process
+//! - **Binary Encoding** - `codec` to encode backtraces compactly for logs and IPC
+//! - **Timestamps** - With the `timestamps` feature, each location shows the time elapsed since the error was created
+//! - **Threads** - With the `threads` feature, locations show the thread and async task they were recorded on
+//! - **Stack Traces** - With the `std-backtrace` feature, the call stack is captured where an error chain originates
+//! - **Tracing** - With the `tracing` feature, errors emit `tracing` events and record the active spans where they originate
+//! - **Error Aggregation** - `Errors<E>` to report many independent errors at once
+//!
+//! # `no_std` Support
+//!
+//! `error2` only needs `alloc`. Disable the default `std` feature to use it in
+//! `no_std` crates:
+//!
+//! ```toml
+//! [dependencies]
+//! error2 = { version = "0.13.2", default-features = false, features = ["derive"] }
+//! ```
+//!
+//! Without `std`, interned file names are guarded by a spin lock, and the
+//! `ERROR2_BACKTRACE` environment variable is not read.
 //!
 //! # Quick Start
 //!
@@ -126,7 +147,11 @@
 //! - [`Error2`] - Extends `std::error::Error` with backtrace support
 //! - [`Context`] - Type conversion: `Result<T, Source> -> Result<T, Target>`, `Option<T> -> Result<T, E>`
 //! - [`Attach`] - Record error propagation locations
+//! - [`AttachValue`] - Attach typed values, readable later with [`Backtrace::request_ref`]
 //! - [`RootError`] - Convenience methods for creating root errors
+//! - [`Suppress`] - Keep cleanup errors that happen while handling a primary error
+//! - [`CollectAll`] - Gather every error of an iterator of results into [`Errors`],
+//!   `CollectAllStream` does the same for streams with the `stream` feature
 //!
 //! # Type Erasure
 //!
@@ -176,6 +201,7 @@
 //!
 //! if let Err(e) = outer() {
 //!     let msg = e.backtrace().error_message();
+//! #   let msg = error2::strip_stamps(&msg);
 //!
 //!     // Full error format with multiple locations:
 //!     // MyError: error
@@ -196,24 +222,55 @@
 //! ```
 
 #![cfg_attr(docsrs, feature(doc_cfg))]
+#![no_std]
+
+extern crate alloc;
+#[cfg(any(feature = "std", test))]
+extern crate std;
+
+use alloc::{string::ToString, vec::Vec};
 
 mod _attach;
+mod attach_value;
 mod backtrace;
 mod boxed;
+mod collect_all;
 mod context;
 mod error2;
+mod errors;
 mod extract;
 mod location;
 mod macros;
+mod remote;
 mod root_error;
 mod str_id;
+mod suppress;
+mod sync;
 
 /// Attach adapters for iterators, futures, and streams.
 pub mod attach;
+/// Global capture level of backtraces.
+///
+/// See [`CaptureLevel`](capture::CaptureLevel) for details.
+pub mod capture;
 /// Error kind enum for downcasting [`BoxedError2`].
 ///
 /// See [`ErrorKind`](kind::ErrorKind) for details.
 pub mod kind;
+/// Configurable rendering of backtraces.
+///
+/// See [`Render`](render::Render) for details.
+pub mod render;
+#[doc(hidden)]
+#[cfg(feature = "serde")]
+pub mod serialize;
+/// Integration with `tracing`: events when errors are created and attached,
+/// and the active spans recorded at the origin of a chain.
+///
+/// See [`set_default_level`](trace::set_default_level) for details.
+#[cfg_attr(docsrs, doc(cfg(feature = "tracing")))]
+#[cfg(feature = "tracing")]
+pub mod trace;
 /// Internal transformation traits (not for direct use).
 pub mod transform;
 
@@ -221,8 +278,9 @@ pub mod transform;
 ///
 /// Import with `use error2::prelude::*;` to get:
 /// - [`Error2`] trait
-/// - [`Context`], [`Attach`], [`RootError`] traits
-/// - [`Backtrace`], [`BoxedError2`] types
+/// - [`Context`], [`Attach`], [`AttachValue`], [`RootError`], [`Suppress`], [`CollectAll`] traits,
+///   and `CollectAllStream` with the `stream` feature
+/// - [`Backtrace`], [`BoxedError2`], [`BoxedErrors2`], [`SharedError2`] types
 /// - [`ViaRoot`], [`ViaStd`], [`ViaErr2`] wrappers
 /// - `#[derive(Error2)]` macro (if `derive` feature enabled)
 pub mod prelude {
@@ -231,25 +289,45 @@ pub mod prelude {
     pub use ::error2_derive::Error2;
 
     // traits
-    pub use crate::{Attach as _, Context as _, RootError as _, error2::Error2};
+    #[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+    #[cfg(feature = "stream")]
+    pub use crate::CollectAllStream as _;
+    pub use crate::{
+        Attach as _, AttachValue as _, CollectAll as _, Context as _, RootError as _,
+        Suppress as _, error2::Error2,
+    };
     // types
-    pub use crate::{Backtrace, BoxedError2, ViaErr2, ViaRoot, ViaStd};
+    pub use crate::{Backtrace, BoxedError2, BoxedErrors2, SharedError2, ViaErr2, ViaRoot, ViaStd};
 }
 
 #[cfg_attr(docsrs, doc(cfg(feature = "derive")))]
 #[cfg(feature = "derive")]
 pub use ::error2_derive::Error2;
 
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+#[cfg(feature = "stream")]
+pub use self::collect_all::{CollectAllFuture, CollectAllStream, TryCollectAllFuture};
+#[doc(hidden)]
+pub use self::extract::strip_stamps;
 pub use self::{
     _attach::Attach,
-    backtrace::Backtrace,
-    boxed::{BoxedError2, ViaErr2, ViaRoot, ViaStd},
+    attach_value::AttachValue,
+    backtrace::{Backtrace, BoxedBacktrace, codec},
+    boxed::{BoxedError2, SharedError2, ViaErr2, ViaRoot, ViaStd},
+    collect_all::CollectAll,
     context::Context,
     error2::Error2,
-    location::Location,
+    errors::{BoxedErrors2, Errors},
+    location::{Location, ParseLocationError},
+    remote::RemoteError,
     root_error::RootError,
+    suppress::Suppress,
+};
+pub(crate) use self::{
+    backtrace::BakctraceEntry,
+    extract::{extract_error_message, extract_public_message},
+    str_id::StrId,
 };
-pub(crate) use self::{backtrace::BakctraceEntry, extract::extract_error_message, str_id::StrId};
 
 pub(crate) mod private {
     #[derive(Debug, Clone, Copy)]
@@ -261,9 +339,78 @@ pub(crate) mod private {
 
 #[doc(hidden)]
 pub fn push_error<E: Error2 + ?Sized>(error: &mut E, location: Location) {
-    let display = error.to_string();
+    // checked before `backtrace_mut`, which allocates an empty `BoxedBacktrace`
+    if capture::capture_level() == capture::CaptureLevel::Off
+        || !error.backtrace_mut().records_error()
+    {
+        #[cfg(feature = "tracing")]
+        trace::created(error, None, location);
+
+        return;
+    }
+
+    // the display message is rendered lazily if the error can resolve it later,
+    // pending messages are resolved by their position in `collect_displays`, so
+    // the message is only deferred if it is recorded at the same position
+    let display_depth = error.display_depth();
+    let display = if display_depth == Some(error.backtrace_mut().message_count() + 1) {
+        None
+    } else {
+        Some(error.to_string())
+    };
+
+    #[cfg(feature = "tracing")]
+    trace::created(error, display.as_deref(), location);
+
+    let public = error.public_message();
     let backtrace = error.backtrace_mut();
     let type_name = core::any::type_name::<E>();
 
-    backtrace.push_error(type_name, display, location);
+    backtrace.push_error(type_name, display, public, location);
+}
+
+/// Records `location` in the error's backtrace, unless the capture level skips it.
+pub(crate) fn push_location<E: Error2 + ?Sized>(error: &mut E, location: Location) {
+    // checked before `backtrace_mut`, which allocates an empty `BoxedBacktrace`
+    if capture::capture_level() == capture::CaptureLevel::Full {
+        error.backtrace_mut().push_location(location);
+    }
+}
+
+/// The display messages collected by [`Error2::collect_displays`].
+#[doc(hidden)]
+pub type Displays<'a> = Vec<&'a dyn core::fmt::Display>;
+
+#[doc(hidden)]
+pub fn resolve_displays<'a, E: Error2 + ?Sized>(
+    error: &E,
+    backtrace: &'a Backtrace,
+) -> &'a Backtrace {
+    if backtrace.has_pending_display() {
+        let mut displays = Vec::new();
+        error.collect_displays(&mut displays);
+
+        backtrace.resolve_displays(&displays);
+    }
+
+    backtrace
+}
+
+/// Clones share their entries, so the backtraces of the sources are not copied.
+pub(crate) fn source_backtraces<E: Error2>(sources: &[E]) -> Vec<Backtrace> {
+    sources.iter().map(|e| e.backtrace().clone()).collect()
+}
+
+pub(crate) fn push_error_with_sources<E: Error2 + ?Sized>(
+    error: &mut E,
+    sources: Vec<Backtrace>,
+    location: Location,
+) {
+    push_error(error, location);
+
+    let backtrace = error.backtrace_mut();
+
+    for source in sources {
+        backtrace.push_child(source);
+    }
 }
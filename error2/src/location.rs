@@ -1,4 +1,4 @@
-use std::{fmt, panic};
+use core::{error::Error, fmt, panic};
 
 use crate::StrId;
 
@@ -22,6 +22,33 @@ use crate::StrId;
 ///
 /// Locations are automatically recorded when using `.context()`, `.attach()`,
 /// `.build()`, and other error handling methods.
+///
+/// # String Form
+///
+/// A `Location` is displayed as `file:line:column`. Locations from trusted
+/// input, such as your own configuration, parse back from the same form with
+/// [`Location::parse_trusted`]:
+///
+/// ```
+/// use error2::Location;
+///
+/// let loc = Location::parse_trusted("src/main.rs:10:5").unwrap();
+/// assert_eq!((loc.file(), loc.line(), loc.column()), ("src/main.rs", 10, 5));
+/// assert_eq!(loc.to_string(), "src/main.rs:10:5");
+///
+/// // file names may contain colons, the last two fields are the line and column
+/// let loc = Location::parse_trusted(r"C:\src\main.rs:1:1").unwrap();
+/// assert_eq!(loc.file(), r"C:\src\main.rs");
+/// ```
+///
+/// # Untrusted Input
+///
+/// The file name of a `Location` is `'static`, so creating one from a string
+/// keeps that string allocated for the rest of the process. Neither
+/// [`Location::parse_trusted`] nor deserializing a `Location` should be fed
+/// input from untrusted peers. Deserialize a [`Backtrace`](crate::Backtrace)
+/// instead, it owns the file names of its locations and the type names and
+/// public messages of its errors, and frees them when it is dropped.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Location {
@@ -48,6 +75,20 @@ impl Location {
         Self::from_std(panic::Location::caller())
     }
 
+    /// Captures the caller's location for a backtrace.
+    ///
+    /// When the recording is compiled out, nothing would record the location,
+    /// so the file name is not interned. Tracing events still report it.
+    #[track_caller]
+    #[inline]
+    pub(crate) fn tracked() -> Self {
+        if crate::capture::COMPILED_OUT && !cfg!(feature = "tracing") {
+            Self::uninit()
+        } else {
+            Self::caller()
+        }
+    }
+
     /// Returns the file path.
     #[inline]
     pub fn file(&self) -> &'static str {
@@ -76,6 +117,20 @@ impl Location {
         }
     }
 
+    /// Parses the `file:line:column` form of a location from trusted input.
+    ///
+    /// The file name is kept allocated for the rest of the process, see
+    /// [Untrusted Input](Location#untrusted-input).
+    pub fn parse_trusted(s: &str) -> Result<Self, ParseLocationError> {
+        let (file, line, column) = split(s).ok_or(ParseLocationError(()))?;
+
+        Ok(Self {
+            file: StrId::intern(file),
+            line,
+            column,
+        })
+    }
+
     pub(crate) const fn uninit() -> Self {
         Self {
             file: StrId::uninit(),
@@ -96,6 +151,30 @@ impl fmt::Display for Location {
     }
 }
 
+/// Splits `file:line:column` from the right, so the file name may contain colons.
+pub(crate) fn split(s: &str) -> Option<(&str, u32, u32)> {
+    let mut parts = s.rsplitn(3, ':');
+
+    let column = parts.next()?.parse().ok()?;
+    let line = parts.next()?.parse().ok()?;
+    let file = parts.next()?;
+
+    Some((file, line, column))
+}
+
+/// The error returned when [`Location::parse_trusted`] fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseLocationError(());
+
+impl fmt::Display for ParseLocationError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid location, expected `file:line:column`")
+    }
+}
+
+impl Error for ParseLocationError {}
+
 impl<'a> From<&'a panic::Location<'static>> for Location {
     #[inline]
     fn from(location: &'a panic::Location<'static>) -> Self {
@@ -105,7 +184,7 @@ impl<'a> From<&'a panic::Location<'static>> for Location {
 
 #[cfg(test)]
 mod tests {
-    use std::panic;
+    use core::panic;
 
     use super::*;
 
@@ -164,6 +243,8 @@ mod tests {
     #[cfg(feature = "serde")]
     #[test]
     fn test_serialize_deserialize_locations() {
+        use alloc::{vec, vec::Vec};
+
         macro_rules! location {
             ($file:literal) => {
                 Location {
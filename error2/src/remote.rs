@@ -0,0 +1,175 @@
+use alloc::boxed::Box;
+use core::{
+    any,
+    error::Error,
+    fmt::{self, Debug, Display, Formatter},
+};
+
+use crate::{Backtrace, Error2};
+
+/// An error reconstructed from a backtrace recorded by another process.
+///
+/// After deserializing a [`Backtrace`] received over the wire, `RemoteError`
+/// turns it back into an error that can be propagated like a local one:
+///
+/// - [`Display`] shows the message of the outermost remote error, and
+///   [`type_name`](Self::type_name) its original type name.
+/// - [`Error::source`] walks the remote errors from the outermost to the
+///   innermost, each displaying its own message.
+/// - The backtrace is the received one, so `.attach()` keeps appending local
+///   locations to it, and `.context(ViaErr2)` does not record the remote
+///   error a second time.
+///
+/// A backtrace without any error, for example one recorded with capturing
+/// turned off, displays as `unknown remote error`.
+///
+/// # Example
+///
+/// ```
+/// use std::error::Error;
+///
+/// use error2::{RemoteError, prelude::*};
+///
+/// #[derive(Debug, Error2)]
+/// #[error2(display("quota exceeded"))]
+/// struct QuotaError {
+///     backtrace: Backtrace,
+/// }
+///
+/// #[derive(Debug, Error2)]
+/// #[error2(display("upload failed"))]
+/// struct UploadError {
+///     source: QuotaError,
+/// }
+///
+/// // on the remote side
+/// let error = QuotaError2.fail::<()>().context(UploadError2).unwrap_err();
+/// let received = error.backtrace().clone();
+///
+/// // on the local side
+/// let remote = RemoteError::new(received).attach();
+/// assert_eq!(remote.to_string(), "upload failed");
+/// assert!(remote.type_name().ends_with("UploadError"));
+/// assert_eq!(remote.source().unwrap().to_string(), "quota exceeded");
+///
+/// let boxed: BoxedError2 = Err::<(), _>(remote).context(ViaErr2).unwrap_err();
+/// let msg = boxed.backtrace().error_message();
+/// assert_eq!(msg.matches("UploadError: upload failed").count(), 1);
+/// assert_eq!(msg.matches("\n    at ").count(), 4);
+/// ```
+pub struct RemoteError {
+    frame: Frame,
+    backtrace: Backtrace,
+}
+
+/// A remote error of the chain, linked to the remote error it wraps.
+#[derive(Debug)]
+struct Frame {
+    type_name: Box<str>,
+    display: Option<Box<str>>,
+    source: Option<Box<Frame>>,
+}
+
+impl Display for Frame {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.display {
+            Some(display) => f.write_str(display),
+            // the remote error was dropped before its display message was rendered
+            None => f.write_str(&self.type_name),
+        }
+    }
+}
+
+impl Error for Frame {
+    #[inline]
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source.as_deref().map(|source| source as _)
+    }
+}
+
+impl RemoteError {
+    /// Creates a `RemoteError` from a received backtrace.
+    pub fn new(backtrace: Backtrace) -> Self {
+        let mut frame = None;
+
+        // messages are recorded from the innermost error to the outermost
+        for message in backtrace.messages() {
+            frame = Some(Frame {
+                type_name: message.type_name().into(),
+                display: message.display().map(Box::from),
+                source: frame.map(Box::new),
+            });
+        }
+
+        let frame = frame.unwrap_or(Frame {
+            type_name: any::type_name::<RemoteError>().into(),
+            display: Some(Box::from("unknown remote error")),
+            source: None,
+        });
+
+        RemoteError { frame, backtrace }
+    }
+
+    /// Returns the original type name of the outermost remote error.
+    #[inline]
+    pub fn type_name(&self) -> &str {
+        &self.frame.type_name
+    }
+}
+
+impl From<Backtrace> for RemoteError {
+    #[inline]
+    fn from(backtrace: Backtrace) -> Self {
+        Self::new(backtrace)
+    }
+}
+
+impl Display for RemoteError {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.frame, f)
+    }
+}
+
+impl Debug for RemoteError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            f.debug_struct("RemoteError")
+                .field("type_name", &self.frame.type_name)
+                .field("display", &self.frame.display)
+                .finish_non_exhaustive()
+        } else {
+            Display::fmt(&self.frame, f)?;
+            write!(f, "\n\n")?;
+
+            let m = self.backtrace.error_message();
+            Display::fmt(&m, f)
+        }
+    }
+}
+
+impl Error for RemoteError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Error::source(&self.frame)
+    }
+}
+
+impl Error2 for RemoteError {
+    #[inline]
+    fn backtrace(&self) -> &Backtrace {
+        &self.backtrace
+    }
+
+    #[inline]
+    fn backtrace_mut(&mut self) -> &mut Backtrace {
+        &mut self.backtrace
+    }
+
+    #[cfg(feature = "tracing")]
+    #[inline]
+    fn event_type_name(&self) -> &str {
+        self.type_name()
+    }
+}
@@ -0,0 +1,319 @@
+mod paths;
+#[cfg(feature = "std")]
+mod snippet;
+
+use core::{
+    fmt::{self, Debug, Display, Formatter},
+    ops::Deref,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+pub use self::paths::{FilterAction, FrameFilter, PathRemap, set_frame_filter, set_path_remap};
+pub(crate) use self::paths::{global_frame_filter, global_path_remap};
+#[cfg(feature = "std")]
+pub(crate) use self::snippet::SourceCache;
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub use self::snippet::SourceSnippets;
+use crate::{Backtrace, extract::Options, sync::RwLock};
+
+const DEFAULT_PUBLIC_FALLBACK: &str = "An internal error occurred";
+
+static PUBLIC_FALLBACK: RwLock<&'static str> = RwLock::new(DEFAULT_PUBLIC_FALLBACK);
+
+/// Sets the message used when no error along the chain has a user-facing message.
+///
+/// The default is `"An internal error occurred"`.
+pub fn set_public_fallback(message: &'static str) {
+    *PUBLIC_FALLBACK.write() = message;
+}
+
+/// Returns the message used when no error along the chain has a user-facing message.
+pub fn public_fallback() -> &'static str {
+    *PUBLIC_FALLBACK.read()
+}
+
+static REVEAL: AtomicU8 = AtomicU8::new(Reveal::Never as u8);
+
+/// When the values of fields marked with `#[error2(redact)]` are shown.
+///
+/// Set with [`set_reveal`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum Reveal {
+    /// Always shows `***`. This is the default.
+    #[default]
+    Never,
+    /// Shows the values in builds with `debug_assertions` enabled, `***` otherwise.
+    DebugBuilds,
+    /// Always shows the values.
+    ///
+    /// Only use this for local debugging, the values end up in logs and
+    /// serialized backtraces.
+    Unsafe,
+}
+
+/// Sets when the values of fields marked with `#[error2(redact)]` are shown.
+///
+/// The display messages of errors with redacted fields are recorded in the
+/// backtrace when the errors are created, so backtraces only follow the policy
+/// for errors created afterwards. Formatting an error itself with `Display`
+/// always follows the current policy.
+pub fn set_reveal(reveal: Reveal) {
+    REVEAL.store(reveal as u8, Ordering::Relaxed);
+}
+
+/// Returns when the values of fields marked with `#[error2(redact)]` are shown.
+pub fn reveal() -> Reveal {
+    match REVEAL.load(Ordering::Relaxed) {
+        1 => Reveal::DebugBuilds,
+        2 => Reveal::Unsafe,
+        _ => Reveal::Never,
+    }
+}
+
+fn is_revealed() -> bool {
+    match reveal() {
+        Reveal::Never => false,
+        Reveal::DebugBuilds => cfg!(debug_assertions),
+        Reveal::Unsafe => true,
+    }
+}
+
+/// Masks a value as `***` in `Display` and `Debug`, unless [revealed](set_reveal).
+///
+/// The derive macro wraps fields marked with `#[error2(redact)]` (or its alias
+/// `#[error2(sensitive)]`) in `Redacted` before formatting the display message.
+/// `Redacted` dereferences to the wrapped value, so method calls in display
+/// arguments still work.
+///
+/// ```
+/// use error2::{prelude::*, render::Reveal};
+///
+/// #[derive(Debug, Error2)]
+/// #[error2(display("invalid token `{token}` for user {user}"))]
+/// struct AuthError {
+///     user: String,
+///     #[error2(redact)]
+///     token: String,
+///     backtrace: Backtrace,
+/// }
+///
+/// let err = AuthError2 { user: "alice", token: "s3cr3t" }.build();
+/// assert_eq!(err.to_string(), "invalid token `***` for user alice");
+///
+/// error2::render::set_reveal(Reveal::Unsafe);
+///
+/// // the backtrace keeps the message rendered when the error was created
+/// assert!(!err.backtrace().error_message().contains("s3cr3t"));
+///
+/// let err = AuthError2 { user: "alice", token: "s3cr3t" }.build();
+/// assert_eq!(err.to_string(), "invalid token `s3cr3t` for user alice");
+/// ```
+///
+/// The attribute only changes the generated `Display`, a derived `Debug` still
+/// prints the raw value. To mask it there too, store the field as `Redacted<T>`:
+///
+/// ```
+/// use error2::{prelude::*, render::Redacted};
+///
+/// #[derive(Debug, Error2)]
+/// #[error2(display("invalid token `{token}`"))]
+/// struct AuthError {
+///     #[error2(redact)]
+///     token: Redacted<String>,
+///     backtrace: Backtrace,
+/// }
+///
+/// let err = AuthError2 { token: Redacted("s3cr3t".to_string()) }.build();
+/// assert_eq!(err.to_string(), "invalid token `***`");
+/// assert!(!format!("{:?}", err).contains("s3cr3t"));
+/// ```
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Redacted<T>(pub T);
+
+impl<T> Deref for Redacted<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T: Display> Display for Redacted<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if is_revealed() {
+            Display::fmt(&self.0, f)
+        } else {
+            f.pad("***")
+        }
+    }
+}
+
+impl<T: Debug> Debug for Redacted<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if is_revealed() {
+            Debug::fmt(&self.0, f)
+        } else {
+            f.pad("***")
+        }
+    }
+}
+
+/// A configurable renderer for a [`Backtrace`], created by [`Backtrace::render`].
+///
+/// The default view is the same as [`Backtrace::error_message`], it shows every
+/// frame with its type name, display message and locations.
+///
+/// # Public View
+///
+/// The public view only shows the messages set with `#[error2(public = "...")]`,
+/// from the outermost error inwards. All internal display strings, type names,
+/// locations and attachments are hidden. If no error along the chain has a
+/// user-facing message, the [fallback](set_public_fallback) is shown instead.
+///
+/// ```
+/// use error2::prelude::*;
+///
+/// #[derive(Debug, Error2)]
+/// #[error2(
+///     display("connection to db-7.internal:5432 refused"),
+///     public = "Service temporarily unavailable"
+/// )]
+/// struct DbError {
+///     backtrace: Backtrace,
+/// }
+///
+/// let err = DbError2.build();
+///
+/// let internal = err.backtrace().render().to_string();
+/// assert!(internal.contains("db-7.internal"));
+///
+/// let public = err.backtrace().render().public().to_string();
+/// assert_eq!(public, "Service temporarily unavailable");
+///
+/// let other = ViaRoot("secret token expired").build();
+/// assert_eq!(
+///     other.backtrace().render().public().to_string(),
+///     "An internal error occurred"
+/// );
+/// ```
+///
+/// # Paths and Filtering
+///
+/// File paths are rewritten by the [`PathRemap`] set with [`set_path_remap`],
+/// and locations are hidden or collapsed by the [`FrameFilter`] set with
+/// [`set_frame_filter`]. [`path_remap`](Self::path_remap) and
+/// [`frame_filter`](Self::frame_filter) override them for one rendering.
+///
+/// # Source Snippets
+///
+/// With the `std` feature, [`source_snippets`](Self::source_snippets) shows
+/// the source lines around each location, see [`SourceSnippets`].
+///
+/// # Extended View
+///
+/// With the `std-backtrace` feature, [`extended`](Self::extended) also shows
+/// the call stack captured where the chain originates, below the locations of
+/// the innermost error, see [`Backtrace::stack_trace`].
+#[derive(Debug, Clone, Copy)]
+pub struct Render<'a> {
+    backtrace: &'a Backtrace,
+    public: bool,
+    path_remap: Option<&'a PathRemap>,
+    frame_filter: Option<&'a FrameFilter>,
+    #[cfg(feature = "std")]
+    source_snippets: Option<&'a SourceSnippets>,
+    #[cfg(feature = "std-backtrace")]
+    extended: bool,
+}
+
+impl<'a> Render<'a> {
+    pub(crate) const fn new(backtrace: &'a Backtrace) -> Self {
+        Self {
+            backtrace,
+            public: false,
+            path_remap: None,
+            frame_filter: None,
+            #[cfg(feature = "std")]
+            source_snippets: None,
+            #[cfg(feature = "std-backtrace")]
+            extended: false,
+        }
+    }
+
+    /// Renders the public view, hiding all internal frames and locations.
+    #[inline]
+    #[must_use]
+    pub const fn public(mut self) -> Self {
+        self.public = true;
+        self
+    }
+
+    /// Rewrites file paths with `remap` instead of the one set with
+    /// [`set_path_remap`].
+    #[inline]
+    #[must_use]
+    pub const fn path_remap(mut self, remap: &'a PathRemap) -> Self {
+        self.path_remap = Some(remap);
+        self
+    }
+
+    /// Filters locations with `filter` instead of the one set with
+    /// [`set_frame_filter`].
+    #[inline]
+    #[must_use]
+    pub const fn frame_filter(mut self, filter: &'a FrameFilter) -> Self {
+        self.frame_filter = Some(filter);
+        self
+    }
+
+    /// Shows the source lines around each location, read from disk.
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    #[cfg(feature = "std")]
+    #[inline]
+    #[must_use]
+    pub const fn source_snippets(mut self, snippets: &'a SourceSnippets) -> Self {
+        self.source_snippets = Some(snippets);
+        self
+    }
+
+    /// Renders the extended view, which also shows the captured call stacks.
+    #[cfg_attr(docsrs, doc(cfg(feature = "std-backtrace")))]
+    #[cfg(feature = "std-backtrace")]
+    #[inline]
+    #[must_use]
+    pub const fn extended(mut self) -> Self {
+        self.extended = true;
+        self
+    }
+}
+
+impl Display for Render<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let rendered = if self.public {
+            crate::extract_public_message(self.backtrace)
+        } else {
+            let path_remap = global_path_remap();
+            let frame_filter = global_frame_filter();
+            #[cfg(feature = "std")]
+            let source_cache = SourceCache::default();
+
+            let options = Options {
+                path_remap: self.path_remap.or(path_remap.as_deref()),
+                frame_filter: self.frame_filter.or(frame_filter.as_deref()),
+                #[cfg(feature = "std")]
+                source_snippets: self
+                    .source_snippets
+                    .map(|source_snippets| (source_snippets, &source_cache)),
+                #[cfg(feature = "std-backtrace")]
+                extended: self.extended,
+            };
+
+            crate::extract::extract_error_message_with(self.backtrace, &options)
+        };
+
+        f.write_str(&rendered)
+    }
+}
@@ -0,0 +1,364 @@
+use alloc::{borrow::Cow, boxed::Box, string::String, sync::Arc, vec::Vec};
+
+use crate::sync::RwLock;
+
+static PATH_REMAP: RwLock<Option<Arc<PathRemap>>> = RwLock::new(None);
+
+static FRAME_FILTER: RwLock<Option<Arc<FrameFilter>>> = RwLock::new(None);
+
+/// Sets how file paths are rewritten when rendering backtraces.
+///
+/// This applies to [`Backtrace::error_message`](crate::Backtrace::error_message)
+/// and to every [`Render`](super::Render) that does not set its own
+/// [`path_remap`](super::Render::path_remap).
+pub fn set_path_remap(remap: PathRemap) {
+    *PATH_REMAP.write() = Some(Arc::new(remap));
+}
+
+/// Sets which locations are hidden or collapsed when rendering backtraces.
+///
+/// This applies to [`Backtrace::error_message`](crate::Backtrace::error_message)
+/// and to every [`Render`](super::Render) that does not set its own
+/// [`frame_filter`](super::Render::frame_filter).
+pub fn set_frame_filter(filter: FrameFilter) {
+    *FRAME_FILTER.write() = Some(Arc::new(filter));
+}
+
+pub(crate) fn global_path_remap() -> Option<Arc<PathRemap>> {
+    PATH_REMAP.read().clone()
+}
+
+pub(crate) fn global_frame_filter() -> Option<Arc<FrameFilter>> {
+    FRAME_FILTER.read().clone()
+}
+
+const REGISTRY_DIRS: [&str; 2] = ["/registry/src/", "\\registry\\src\\"];
+
+const GIT_CHECKOUT_DIRS: [&str; 2] = ["/git/checkouts/", "\\git\\checkouts\\"];
+
+fn is_separator(c: char) -> bool {
+    c == '/' || c == '\\'
+}
+
+#[derive(Debug, Clone)]
+enum RemapRule {
+    Prefix { from: Box<str>, to: Box<str> },
+    Registry,
+}
+
+impl RemapRule {
+    fn apply<'p>(&self, path: &'p str) -> Option<Cow<'p, str>> {
+        match self {
+            RemapRule::Prefix { from, to } => {
+                let rest = path.strip_prefix(&**from)?;
+
+                // `/ci/build` must not match `/ci/builder/src/lib.rs`
+                if !(rest.is_empty()
+                    || from.ends_with(is_separator)
+                    || rest.starts_with(is_separator))
+                {
+                    return None;
+                }
+
+                if to.is_empty() {
+                    Some(Cow::Borrowed(rest.trim_start_matches(is_separator)))
+                } else {
+                    let mut remapped = String::with_capacity(to.len() + rest.len());
+                    remapped.push_str(to);
+                    remapped.push_str(rest);
+                    Some(Cow::Owned(remapped))
+                }
+            }
+            RemapRule::Registry => {
+                let (start, dir) = REGISTRY_DIRS
+                    .iter()
+                    .find_map(|dir| path.find(dir).map(|start| (start, dir)))?;
+
+                // skip the index directory, such as `index.crates.io-6f17d22bba15001f`
+                let index = &path[start + dir.len()..];
+                let end = index.find(is_separator)?;
+
+                Some(Cow::Borrowed(&index[end + 1..]))
+            }
+        }
+    }
+}
+
+/// Rewrites the file paths of rendered locations.
+///
+/// `file!()` and `#[track_caller]` record paths the way they were passed to the
+/// compiler: absolute build directories for some builds, and paths into
+/// `~/.cargo/registry` for dependencies. `PathRemap` shortens them when
+/// rendering, the recorded locations themselves are unchanged.
+///
+/// Rules are tried in the order they were added, the first one that matches
+/// rewrites the path.
+///
+/// # Example
+///
+/// ```
+/// use error2::{
+///     Location,
+///     prelude::*,
+///     render::{PathRemap, set_path_remap},
+/// };
+///
+/// set_path_remap(
+///     PathRemap::new()
+///         .strip_prefix("/ci/build/my-service")
+///         .registry()
+///         .prefix("/opt/vendor", "vendor"),
+/// );
+///
+/// let error = BoxedError2::from_root("timeout")
+///     .attach_location(Location::new("/ci/build/my-service/src/main.rs", 7, 5))
+///     .attach_location(Location::new(
+///         "/home/ci/.cargo/registry/src/index.crates.io-6f17d22bba15001f/tokio-1.47.1/src/time.rs",
+///         12,
+///         9,
+///     ))
+///     .attach_location(Location::new("/opt/vendor/codec/src/lib.rs", 3, 1));
+///
+/// let msg = error.backtrace().error_message();
+/// # let msg = error2::strip_stamps(&msg);
+/// assert!(msg.ends_with(concat!(
+///     "\n    at src/main.rs:7:5",
+///     "\n    at tokio-1.47.1/src/time.rs:12:9",
+///     "\n    at vendor/codec/src/lib.rs:3:1",
+/// )));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PathRemap {
+    rules: Vec<RemapRule>,
+}
+
+impl PathRemap {
+    /// Creates a `PathRemap` without any rules, paths are shown as recorded.
+    #[inline]
+    pub const fn new() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    /// Replaces the leading `from` of a path with `to`.
+    ///
+    /// `from` only matches whole path components, so `/ci/build` does not
+    /// match `/ci/builder/src/lib.rs`.
+    #[must_use]
+    pub fn prefix(mut self, from: impl Into<Box<str>>, to: impl Into<Box<str>>) -> Self {
+        self.rules.push(RemapRule::Prefix {
+            from: from.into(),
+            to: to.into(),
+        });
+        self
+    }
+
+    /// Removes a leading prefix, such as the workspace root, and the path
+    /// separator that follows it.
+    #[inline]
+    #[must_use]
+    pub fn strip_prefix(self, prefix: impl Into<Box<str>>) -> Self {
+        self.prefix(prefix, "")
+    }
+
+    /// Shortens paths into the cargo registry to the crate directory, so
+    /// `~/.cargo/registry/src/<index>/serde-1.0.219/src/de.rs` becomes
+    /// `serde-1.0.219/src/de.rs`.
+    #[must_use]
+    pub fn registry(mut self) -> Self {
+        self.rules.push(RemapRule::Registry);
+        self
+    }
+
+    /// Returns the rewritten path, or `path` itself if no rule matches.
+    pub fn apply<'p>(&self, path: &'p str) -> Cow<'p, str> {
+        self.rules
+            .iter()
+            .find_map(|rule| rule.apply(path))
+            .unwrap_or(Cow::Borrowed(path))
+    }
+}
+
+/// What happens to the locations matched by a [`FrameFilter`] rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FilterAction {
+    /// The locations are left out of the rendered backtrace.
+    Hide,
+    /// Each run of consecutive matched locations is replaced by a single
+    /// `… N filtered locations` line.
+    Collapse,
+}
+
+#[derive(Debug, Clone)]
+enum Matcher {
+    Dependencies,
+    Glob(Box<str>),
+}
+
+/// Hides or collapses locations when rendering backtraces.
+///
+/// Rules are tried in the order they were added, the first one that matches a
+/// location decides its [`FilterAction`]. Locations that match no rule are
+/// shown. Error messages, notes and nested backtraces are never filtered.
+///
+/// # Example
+///
+/// ```
+/// use error2::{
+///     Location,
+///     prelude::*,
+///     render::{FilterAction, FrameFilter},
+/// };
+///
+/// let error = BoxedError2::from_root("timeout")
+///     .attach_location(Location::new(
+///         "/home/ci/.cargo/registry/src/index.crates.io-6f17d22bba15001f/tower-0.5.2/src/retry.rs",
+///         40,
+///         9,
+///     ))
+///     .attach_location(Location::new(
+///         "/home/ci/.cargo/registry/src/index.crates.io-6f17d22bba15001f/tower-0.5.2/src/util.rs",
+///         18,
+///         5,
+///     ))
+///     .attach_location(Location::new("src/generated/api.rs", 3, 1))
+///     .attach_location(Location::new("src/main.rs", 9, 1));
+///
+/// let filter = FrameFilter::new()
+///     .dependencies(FilterAction::Collapse)
+///     .glob("src/generated/*", FilterAction::Hide);
+///
+/// let rendered = error.backtrace().render().frame_filter(&filter).to_string();
+/// # let rendered = error2::strip_stamps(&rendered);
+/// assert!(rendered.ends_with(concat!(
+///     "\n    … 2 filtered locations",
+///     "\n    at src/main.rs:9:1",
+/// )));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FrameFilter {
+    rules: Vec<(Matcher, FilterAction)>,
+}
+
+impl FrameFilter {
+    /// Creates a `FrameFilter` without any rules, all locations are shown.
+    #[inline]
+    pub const fn new() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    /// Matches locations in dependency crates, from the cargo registry or
+    /// from git checkouts.
+    ///
+    /// This looks at the recorded path, so it keeps working when the path is
+    /// shortened by a [`PathRemap`].
+    #[must_use]
+    pub fn dependencies(mut self, action: FilterAction) -> Self {
+        self.rules.push((Matcher::Dependencies, action));
+        self
+    }
+
+    /// Matches locations whose rendered path, after the [`PathRemap`], matches
+    /// a glob pattern.
+    ///
+    /// `*` matches any run of characters, including path separators, and `?`
+    /// matches a single character.
+    #[must_use]
+    pub fn glob(mut self, pattern: impl Into<Box<str>>, action: FilterAction) -> Self {
+        self.rules.push((Matcher::Glob(pattern.into()), action));
+        self
+    }
+
+    /// Returns the action for a location, given its recorded and rendered paths.
+    pub(crate) fn action(&self, recorded: &str, rendered: &str) -> Option<FilterAction> {
+        self.rules.iter().find_map(|(matcher, action)| {
+            let matched = match matcher {
+                Matcher::Dependencies => REGISTRY_DIRS
+                    .iter()
+                    .chain(&GIT_CHECKOUT_DIRS)
+                    .any(|dir| recorded.contains(dir)),
+                Matcher::Glob(pattern) => glob_match(pattern, rendered),
+            };
+
+            matched.then_some(*action)
+        })
+    }
+}
+
+/// Matches `text` against a pattern where `*` matches any run of characters
+/// and `?` a single one.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+
+    let (mut p, mut t) = (0, 0);
+    // the position of the last `*` and the text position it is tried from
+    let mut backtrack = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                // let the last `*` consume one more character
+                Some((star, from)) => {
+                    backtrack = Some((star, from + 1));
+                    p = star + 1;
+                    t = from + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("src/*.rs", "src/main.rs"));
+        assert!(glob_match("src/*.rs", "src/a/b.rs"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("?.rs", "é.rs"));
+        assert!(glob_match("*tower-*", "tower-0.5.2/src/retry.rs"));
+        assert!(glob_match("a*b*c", "abxbc"));
+        assert!(!glob_match("src/*.rs", "src/main.rs.bak"));
+        assert!(!glob_match("?.rs", "ab.rs"));
+        assert!(!glob_match("a*b*c", "abxb"));
+    }
+
+    #[test]
+    fn test_remap_rules() {
+        let remap = PathRemap::new()
+            .strip_prefix("/ci/build")
+            .registry()
+            .prefix("C:\\work", "work");
+
+        assert_eq!(remap.apply("/ci/build/src/lib.rs"), "src/lib.rs");
+        assert_eq!(
+            remap.apply("/ci/builder/src/lib.rs"),
+            "/ci/builder/src/lib.rs"
+        );
+        assert_eq!(
+            remap.apply(
+                "C:\\Users\\ci\\.cargo\\registry\\src\\index.crates.io-6f17d22bba15001f\\serde-1.0.219\\src\\de.rs"
+            ),
+            "serde-1.0.219\\src\\de.rs"
+        );
+        assert_eq!(remap.apply("C:\\work\\src\\lib.rs"), "work\\src\\lib.rs");
+        assert_eq!(remap.apply("src/lib.rs"), "src/lib.rs");
+        assert!(matches!(
+            remap.apply("/ci/build/src/lib.rs"),
+            Cow::Borrowed(_)
+        ));
+    }
+}
@@ -0,0 +1,288 @@
+use std::{
+    cell::{OnceCell, RefCell},
+    collections::HashMap,
+    env, format,
+    fs::{self, File},
+    io::Read,
+    path::{Path, PathBuf},
+    rc::Rc,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Files larger than this are not shown, 1 MiB.
+const MAX_SOURCE_LEN: u64 = 1 << 20;
+
+/// Shows the source lines around each location when rendering, read from disk.
+///
+/// Enable it for one rendering with
+/// [`Render::source_snippets`](super::Render::source_snippets). Each shown
+/// location is followed by the recorded line, a few lines of context with line
+/// numbers, and a caret under the recorded column. Files that are missing or
+/// unreadable, and lines past the end of a file, are skipped silently, so this
+/// is meant for local development rather than production logs.
+///
+/// Relative paths, including the ones shortened by a
+/// [`PathRemap`](super::PathRemap), are resolved against the
+/// [source root](Self::source_root), or the current directory if there is none.
+/// Only files inside that directory are read: absolute paths elsewhere, such
+/// as the ones of dependencies, and paths leaving it through `..` or symbolic
+/// links are skipped, as are files larger than 1 MiB. Each file is read once
+/// per rendering.
+///
+/// # Example
+///
+/// ```
+/// use error2::{Location, prelude::*, render::SourceSnippets};
+///
+/// let root = std::env::temp_dir().join("error2-snippet-doctest");
+/// std::fs::create_dir_all(root.join("src")).unwrap();
+/// std::fs::write(
+///     root.join("src/main.rs"),
+///     "fn main() {\n    let config = load()?;\n    run(config)\n}\n",
+/// )
+/// .unwrap();
+///
+/// let error = BoxedError2::from_root("config not found")
+///     .attach_location(Location::new("src/main.rs", 2, 18))
+///     .attach_location(Location::new("src/missing.rs", 1, 1));
+///
+/// let snippets = SourceSnippets::new().context(1).source_root(&root);
+/// let rendered = error.backtrace().render().source_snippets(&snippets).to_string();
+/// # let rendered = error2::strip_stamps(&rendered);
+///
+/// assert!(rendered.ends_with(concat!(
+///     "\n    at src/main.rs:2:18",
+///     "\n        1 | fn main() {",
+///     "\n        2 |     let config = load()?;",
+///     "\n          |                  ^",
+///     "\n        3 |     run(config)",
+///     "\n    at src/missing.rs:1:1",
+/// )));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SourceSnippets {
+    context: u32,
+    source_root: Option<PathBuf>,
+}
+
+impl Default for SourceSnippets {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SourceSnippets {
+    /// Creates a `SourceSnippets` showing 2 lines of context around each
+    /// location, with paths resolved against the current directory.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            context: 2,
+            source_root: None,
+        }
+    }
+
+    /// Sets how many lines are shown before and after the recorded line.
+    #[inline]
+    #[must_use]
+    pub const fn context(mut self, lines: u32) -> Self {
+        self.context = lines;
+        self
+    }
+
+    /// Sets the directory relative paths are resolved against, for sources
+    /// checked out somewhere else than where they were built.
+    #[inline]
+    #[must_use]
+    pub fn source_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.source_root = Some(root.into());
+        self
+    }
+
+    /// Appends the snippet of a location to `msg`, or nothing if the source
+    /// cannot be read.
+    pub(crate) fn write(
+        &self,
+        msg: &mut String,
+        cache: &SourceCache,
+        path: &str,
+        line: u32,
+        column: u32,
+    ) {
+        if line == 0 {
+            return;
+        }
+
+        let Some(source) = cache.read(self.source_root.as_deref(), Path::new(path)) else {
+            return;
+        };
+
+        let first = line.saturating_sub(self.context).max(1);
+        let last = line.saturating_add(self.context);
+        let lines = source
+            .lines()
+            .zip(1..)
+            .skip(first as usize - 1)
+            .take_while(|(_, number)| *number <= last)
+            .collect::<Vec<_>>();
+
+        if !lines.iter().any(|(_, number)| *number == line) {
+            return;
+        }
+
+        let width = lines
+            .last()
+            .map_or(0, |(_, number)| number.to_string().len());
+
+        for (text, number) in lines {
+            msg.push_str(&format!("\n        {:>width$} |", number, width = width));
+
+            if !text.is_empty() {
+                msg.push(' ');
+                msg.push_str(text);
+            }
+
+            if number == line && column > 0 {
+                // keep tabs so the caret lines up with the text above it, a
+                // column past the end of the line points right after it
+                let padding = text
+                    .chars()
+                    .take(column as usize - 1)
+                    .map(|c| if c == '\t' { '\t' } else { ' ' })
+                    .collect::<String>();
+
+                msg.push_str(&format!(
+                    "\n        {:width$} | {}^",
+                    "",
+                    padding,
+                    width = width
+                ));
+            }
+        }
+    }
+}
+
+/// The sources read during one rendering, so that a file is read once however
+/// many of its locations are shown.
+#[derive(Default)]
+pub(crate) struct SourceCache {
+    /// The canonical source root, `None` if it cannot be resolved.
+    root: OnceCell<Option<PathBuf>>,
+    files: RefCell<HashMap<PathBuf, Option<Rc<str>>>>,
+}
+
+impl SourceCache {
+    fn read(&self, root: Option<&Path>, path: &Path) -> Option<Rc<str>> {
+        let root = self
+            .root
+            .get_or_init(|| {
+                let root = match root {
+                    Some(root) => root.to_path_buf(),
+                    None => env::current_dir().ok()?,
+                };
+
+                fs::canonicalize(root).ok()
+            })
+            .as_deref()?;
+
+        // `..` and symbolic links are resolved before checking the root
+        let path = fs::canonicalize(root.join(path)).ok()?;
+        if !path.starts_with(root) {
+            return None;
+        }
+
+        self.files
+            .borrow_mut()
+            .entry(path)
+            .or_insert_with_key(|path| read_limited(path).map(Rc::from))
+            .clone()
+    }
+}
+
+fn read_limited(path: &Path) -> Option<String> {
+    let mut source = String::new();
+
+    // the metadata may not know the length, e.g. for a FIFO
+    File::open(path)
+        .ok()?
+        .take(MAX_SOURCE_LEN + 1)
+        .read_to_string(&mut source)
+        .ok()?;
+
+    (source.len() as u64 <= MAX_SOURCE_LEN).then_some(source)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::string::ToString;
+
+    use super::SourceSnippets;
+    use crate::{Attach, BoxedError2, Error2, Location, extract::strip_stamps};
+
+    #[test]
+    fn test_snippet_follows_repeated_locations() {
+        let root = std::env::temp_dir().join("error2-snippet-test");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("retry.rs"), "loop {\n\tretry()?;\n}").unwrap();
+
+        let retry = Location::new("retry.rs", 2, 7);
+        let error = BoxedError2::from_root("timeout")
+            .attach_location(retry)
+            .attach_location(retry)
+            .attach_location(Location::new("retry.rs", 9, 1));
+
+        let snippets = SourceSnippets::new().context(0).source_root(&root);
+        let rendered = strip_stamps(
+            &error
+                .backtrace()
+                .render()
+                .source_snippets(&snippets)
+                .to_string(),
+        );
+
+        // line 9 is past the end of the file
+        assert!(rendered.ends_with(concat!(
+            "\n    at retry.rs:2:7 (repeated 2 times)",
+            "\n        2 | \tretry()?;",
+            "\n          | \t     ^",
+            "\n    at retry.rs:9:1",
+        )));
+    }
+
+    #[test]
+    fn test_snippet_stays_inside_the_source_root() {
+        let root = std::env::temp_dir().join("error2-snippet-root/src");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("main.rs"), "fn main() {}").unwrap();
+        let outside = root.parent().unwrap().join("secret.rs");
+        std::fs::write(&outside, "let key = 42;").unwrap();
+
+        let error = BoxedError2::from_root("timeout")
+            .attach_location(Location::new("main.rs", 1, u32::MAX))
+            .attach_location(Location::new("../secret.rs", 1, 1))
+            .attach_location(Location::new(
+                outside.to_str().unwrap().to_string().leak(),
+                1,
+                1,
+            ));
+
+        let snippets = SourceSnippets::new().context(0).source_root(&root);
+        let rendered = strip_stamps(
+            &error
+                .backtrace()
+                .render()
+                .source_snippets(&snippets)
+                .to_string(),
+        );
+
+        // a column past the end of the line points right after it
+        assert!(rendered.contains(concat!(
+            "\n    at main.rs:1:4294967295",
+            "\n        1 | fn main() {}",
+            "\n          |             ^",
+        )));
+        assert!(!rendered.contains("key"));
+    }
+}
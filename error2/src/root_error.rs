@@ -77,6 +77,7 @@ use crate::{Error2, Location, transform::SourceToTarget};
 ///
 /// let err = InvalidId2 { id: -1 }.build();
 /// let msg = err.backtrace().error_message();
+/// # let msg = error2::strip_stamps(&msg);
 ///
 /// // Full error format with location tracking:
 /// // AppError: invalid ID: -1
@@ -97,7 +98,7 @@ pub trait RootError<M, Target: Error2>: SourceToTarget<M, (), (), Target> + Size
     #[must_use]
     #[track_caller]
     fn build(self) -> Target {
-        self.build_with_location(Location::caller())
+        self.build_with_location(Location::tracked())
     }
 
     /// Creates a root error with explicit location.
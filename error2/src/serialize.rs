@@ -0,0 +1,33 @@
+//! Support for the `serialize` and `deserialize` attributes of `#[derive(Error2)]`.
+
+use alloc::string::String;
+use core::fmt::Display;
+
+pub use serde;
+
+/// Serializes a value as its display message.
+pub struct Displayed<'a, T: ?Sized>(pub &'a T);
+
+impl<T: Display + ?Sized> serde::Serialize for Displayed<'_, T> {
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self.0)
+    }
+}
+
+/// Deserializes a std source from the display message it was serialized as,
+/// through its `From<String>` implementation.
+pub struct FromDisplay<T>(pub T);
+
+impl<'de, T: From<String>> serde::Deserialize<'de> for FromDisplay<T> {
+    #[inline]
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        String::deserialize(deserializer).map(|display| Self(T::from(display)))
+    }
+}
@@ -0,0 +1,48 @@
+use core::cell::Cell;
+
+use super::StrId;
+
+const SLOTS: usize = 64;
+
+/// The address and length of an interned `&'static str`, and its id.
+///
+/// Static strings are never freed or changed, so two of them with the same
+/// address and length have the same content.
+type Slot = (usize, usize, StrId);
+
+const EMPTY: Slot = (0, 0, StrId::uninit());
+
+std::thread_local! {
+    static CACHE: [Cell<Slot>; SLOTS] = const { [const { Cell::new(EMPTY) }; SLOTS] };
+}
+
+#[inline]
+fn slot_of(s: &'static str) -> (usize, (usize, usize)) {
+    let key = (s.as_ptr() as usize, s.len());
+    let hash = (key.0 as u64 ^ key.1 as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+
+    ((hash >> (u64::BITS - SLOTS.trailing_zeros())) as usize, key)
+}
+
+/// Returns the id of `s` if it is cached in this thread, looked up by the
+/// address of `s`, so that the string itself is not hashed.
+#[inline]
+pub(super) fn get(s: &'static str) -> Option<StrId> {
+    let (index, key) = slot_of(s);
+
+    CACHE
+        .try_with(|cache| {
+            let (ptr, len, id) = cache[index].get();
+            ((ptr, len) == key).then_some(id)
+        })
+        .ok()
+        .flatten()
+}
+
+#[inline]
+pub(super) fn insert(s: &'static str, id: StrId) {
+    let (index, key) = slot_of(s);
+
+    // ignored if the thread is being torn down
+    let _ = CACHE.try_with(|cache| cache[index].set((key.0, key.1, id)));
+}
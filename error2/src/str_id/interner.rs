@@ -1,47 +1,137 @@
-use std::sync::Arc;
+#[cfg(feature = "std")]
+pub(super) use self::concurrent::Interner;
+#[cfg(not(feature = "std"))]
+pub(super) use self::spin_lock::Interner;
 
-use append_only_vec::AppendOnlyVec;
-use scc::HashIndex;
+#[cfg(feature = "std")]
+mod concurrent {
+    use std::sync::Arc;
 
-use super::{StrId, small_string::SmallString};
+    use append_only_vec::AppendOnlyVec;
+    use scc::HashIndex;
 
-#[derive(Debug, Default)]
-pub(super) struct Interner {
-    vec: AppendOnlyVec<SmallString>,
-    map: HashIndex<SmallString, StrId>,
+    use super::super::{StrId, small_string::SmallString};
+
+    #[derive(Debug, Default)]
+    pub(in crate::str_id) struct Interner {
+        vec: AppendOnlyVec<SmallString>,
+        map: HashIndex<SmallString, StrId>,
+    }
+
+    impl Interner {
+        pub(in crate::str_id) fn intern_static(&self, s: &'static str) -> StrId {
+            if self.is_full() {
+                return self.get(s);
+            }
+
+            *self
+                .map
+                .entry_sync(SmallString::Borrowed(s))
+                .or_insert_with(|| self.push(SmallString::Borrowed(s)))
+        }
+
+        pub(in crate::str_id) fn intern_normal(&self, s: &str) -> StrId {
+            if let Some(id) = self.map.get_sync(s) {
+                return *id.get();
+            }
+
+            if self.is_full() {
+                return self.get(s);
+            }
+
+            let s: Arc<str> = s.into();
+            *self
+                .map
+                .entry_sync(SmallString::Owned(s.clone()))
+                .or_insert_with(|| self.push(SmallString::Owned(s)))
+        }
+
+        /// Returns `true` once the ids ran out, new strings are not stored anymore.
+        fn is_full(&self) -> bool {
+            StrId::new(self.vec.len()).is_none()
+        }
+
+        fn get(&self, s: &str) -> StrId {
+            self.map
+                .get_sync(s)
+                .map_or(StrId::exhausted(), |id| *id.get())
+        }
+
+        /// Stores a new string, its id is its index even if other threads push concurrently.
+        fn push(&self, s: SmallString) -> StrId {
+            StrId::new(self.vec.push(s)).unwrap_or(StrId::exhausted())
+        }
+
+        #[inline]
+        pub(in crate::str_id) fn lookup(&self, id: &StrId) -> &str {
+            self.vec[id.inner()].as_str()
+        }
+    }
 }
 
-impl Interner {
-    pub(super) fn intern_static(&self, s: &'static str) -> StrId {
-        *self
-            .map
-            .entry_sync(SmallString::Borrowed(s))
-            .or_insert_with(|| {
-                let id = StrId::new(self.vec.len());
-                self.vec.push(SmallString::Borrowed(s));
-                id
-            })
+/// Interner for `no_std`, a spin lock guards both the strings and the index.
+#[cfg(not(feature = "std"))]
+mod spin_lock {
+    use alloc::{boxed::Box, collections::BTreeMap, vec::Vec};
+
+    use spin::Mutex;
+
+    use super::super::StrId;
+
+    pub(in crate::str_id) struct Interner {
+        inner: Mutex<Inner>,
+    }
+
+    struct Inner {
+        vec: Vec<&'static str>,
+        map: BTreeMap<&'static str, StrId>,
     }
 
-    #[allow(dead_code)]
-    pub(super) fn intern_normal(&self, s: &str) -> StrId {
-        if let Some(id) = self.map.get_sync(s) {
-            return *id.get();
+    impl Interner {
+        pub(in crate::str_id) const fn new() -> Self {
+            Self {
+                inner: Mutex::new(Inner {
+                    vec: Vec::new(),
+                    map: BTreeMap::new(),
+                }),
+            }
         }
 
-        let id = StrId::new(self.vec.len());
+        pub(in crate::str_id) fn intern_static(&self, s: &'static str) -> StrId {
+            self.inner.lock().intern(s)
+        }
+
+        pub(in crate::str_id) fn intern_normal(&self, s: &str) -> StrId {
+            let mut inner = self.inner.lock();
 
-        let s: Arc<str> = s.into();
-        self.vec.push(SmallString::Owned(s.clone()));
-        self.map
-            .insert_sync(SmallString::Owned(s), id)
-            .expect("unreachable, if we have a collision, we should have found it before");
+            match inner.map.get(s) {
+                Some(id) => *id,
+                None if StrId::new(inner.vec.len()).is_none() => StrId::exhausted(),
+                // interned strings live as long as the program, like those of the `std` interner
+                None => inner.intern(Box::leak(Box::from(s))),
+            }
+        }
 
-        id
+        #[inline]
+        pub(in crate::str_id) fn lookup(&self, id: &StrId) -> &'static str {
+            self.inner.lock().vec[id.inner()]
+        }
     }
 
-    #[inline]
-    pub(super) fn lookup(&self, id: &StrId) -> &str {
-        self.vec[id.inner()].as_str()
+    impl Inner {
+        fn intern(&mut self, s: &'static str) -> StrId {
+            if let Some(id) = self.map.get(s) {
+                return *id;
+            }
+
+            // new strings are not stored once the ids ran out
+            let Some(id) = StrId::new(self.vec.len()) else {
+                return StrId::exhausted();
+            };
+
+            self.vec.push(s);
+            self.map.insert(s, id);
+            id
+        }
     }
 }
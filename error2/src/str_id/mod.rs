@@ -1,61 +1,109 @@
+#[cfg(feature = "std")]
+mod cache;
 mod interner;
+#[cfg(feature = "std")]
 mod small_string;
 
-use std::{fmt, sync::LazyLock};
+use core::{fmt, num::NonZeroU32};
+#[cfg(feature = "std")]
+use std::sync::LazyLock;
 
 use self::interner::Interner;
 
+#[cfg(feature = "std")]
 static INTERNER: LazyLock<Interner> = LazyLock::new(Interner::default);
 
+#[cfg(not(feature = "std"))]
+static INTERNER: Interner = Interner::new();
+
+/// The number of strings that can be interned, the two largest ids are
+/// [`uninit`](StrId::uninit) and [`exhausted`](StrId::exhausted).
+const MAX_STRINGS: usize = u32::MAX as usize - 2;
+
+/// Shown in place of the strings interned after the ids ran out.
+const EXHAUSTED: &str = "<too many interned strings>";
+
+/// The id of an interned string, stored plus one so that the types holding
+/// a `StrId` have a niche.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub(crate) struct StrId(u32);
+pub(crate) struct StrId(NonZeroU32);
 
 impl StrId {
-    pub(crate) const fn new(id: usize) -> Self {
-        assert!(id < (u32::MAX as usize));
-        StrId(id as u32)
+    /// Returns the id of the `index`-th interned string, or `None` if the ids ran out.
+    pub(crate) const fn new(index: usize) -> Option<Self> {
+        if index < MAX_STRINGS {
+            Some(StrId(NonZeroU32::new(index as u32 + 1).unwrap()))
+        } else {
+            None
+        }
     }
 
     pub(crate) const fn uninit() -> Self {
-        StrId(u32::MAX)
+        StrId(NonZeroU32::MAX)
+    }
+
+    /// The id of every string interned after the ids ran out.
+    pub(crate) const fn exhausted() -> Self {
+        StrId(NonZeroU32::new(u32::MAX - 1).unwrap())
     }
 
     pub(crate) const fn is_uninit(&self) -> bool {
-        self.0 == u32::MAX
+        self.0.get() == u32::MAX
     }
 
     pub(crate) const fn inner(&self) -> usize {
-        self.0 as usize
+        self.0.get() as usize - 1
+    }
+
+    /// Interns a string that is not `'static`, the copy lives for the rest of the process.
+    pub(crate) fn intern(s: &str) -> Self {
+        INTERNER.intern_normal(s)
+    }
+
+    fn as_str(&self) -> &'static str {
+        if *self == Self::exhausted() {
+            EXHAUSTED
+        } else {
+            INTERNER.lookup(self)
+        }
     }
 }
 
 impl fmt::Debug for StrId {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let s = INTERNER.lookup(self);
-        fmt::Debug::fmt(s, f)
+        fmt::Debug::fmt(self.as_str(), f)
     }
 }
 
 impl fmt::Display for StrId {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let s = INTERNER.lookup(self);
-        fmt::Display::fmt(s, f)
+        fmt::Display::fmt(self.as_str(), f)
     }
 }
 
 impl From<&'static str> for StrId {
     #[inline]
     fn from(s: &'static str) -> Self {
-        INTERNER.intern_static(s)
+        #[cfg(feature = "std")]
+        if let Some(id) = cache::get(s) {
+            return id;
+        }
+
+        let id = INTERNER.intern_static(s);
+
+        #[cfg(feature = "std")]
+        cache::insert(s, id);
+
+        id
     }
 }
 
 impl From<StrId> for &'static str {
     #[inline]
     fn from(id: StrId) -> Self {
-        INTERNER.lookup(&id)
+        id.as_str()
     }
 }
 
@@ -67,8 +115,7 @@ impl serde::Serialize for StrId {
     where
         S: serde::Serializer,
     {
-        let s = INTERNER.lookup(self);
-        serializer.serialize_str(s)
+        serializer.serialize_str(self.as_str())
     }
 }
 
@@ -81,7 +128,36 @@ impl<'de> serde::Deserialize<'de> for StrId {
         D: serde::Deserializer<'de>,
     {
         let s: &str = serde::Deserialize::deserialize(deserializer)?;
-        let id = INTERNER.intern_normal(s);
-        Ok(id)
+        Ok(StrId::intern(s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{boxed::Box, string::String};
+
+    use super::StrId;
+
+    #[test]
+    fn test_equal_strings_at_different_addresses() {
+        let a: &'static str = Box::leak(Box::from("src/cache.rs"));
+        let b: &'static str = Box::leak(String::from("src/cache.rs").into_boxed_str());
+        assert_ne!(a.as_ptr(), b.as_ptr());
+
+        // the second lookup of each string hits the per-thread cache
+        for _ in 0..2 {
+            assert_eq!(StrId::from(a), StrId::from(b));
+            assert_eq!(<&str>::from(StrId::from(b)), "src/cache.rs");
+        }
+    }
+
+    #[test]
+    fn test_ids_run_out_without_panicking() {
+        assert_eq!(StrId::new(0).unwrap().inner(), 0);
+        assert!(StrId::new(super::MAX_STRINGS).is_none());
+
+        let exhausted = StrId::exhausted();
+        assert!(!exhausted.is_uninit());
+        assert_eq!(<&str>::from(exhausted), super::EXHAUSTED);
     }
 }
@@ -0,0 +1,83 @@
+use core::mem;
+
+use crate::{Backtrace, Error2};
+
+/// Keep secondary errors that happen while a primary error is being handled.
+///
+/// When a cleanup step such as a rollback or a close fails while an error is
+/// already propagating, `.suppress()` stores the secondary error's backtrace on the
+/// primary error, in the spirit of Java's `addSuppressed`. Suppressed errors are
+/// rendered under a `suppressed:` label below the frame they were added to, and
+/// are part of the serialized backtrace.
+///
+/// # Example
+///
+/// ```
+/// use error2::prelude::*;
+/// use regex::Regex;
+///
+/// #[derive(Debug, Error2)]
+/// #[error2(display("commit failed"))]
+/// struct CommitError {
+///     backtrace: Backtrace,
+/// }
+///
+/// #[derive(Debug, Error2)]
+/// #[error2(display("rollback failed"))]
+/// struct RollbackError {
+///     backtrace: Backtrace,
+/// }
+///
+/// fn rollback() -> Result<(), RollbackError> {
+///     RollbackError2.fail()
+/// }
+///
+/// fn commit() -> Result<(), CommitError> {
+///     let mut error = CommitError2.build();
+///
+///     if let Err(e) = rollback() {
+///         error = error.suppress(e);
+///     }
+///
+///     Err(error)
+/// }
+///
+/// let err = commit().unwrap_err();
+/// assert_eq!(err.backtrace().suppressed().count(), 1);
+///
+/// let msg = err.backtrace().error_message();
+/// # let msg = error2::strip_stamps(&msg);
+///
+/// // CommitError: commit failed
+/// //     at /path/to/file.rs:35:30
+/// //     suppressed: RollbackError: rollback failed
+/// //             at /path/to/file.rs:30:19
+///
+/// let re = Regex::new(concat!(
+///     r"(?s)^.+CommitError: commit failed",
+///     r"\n    at .+\.rs:\d+:\d+",
+///     r"\n    suppressed: .+RollbackError: rollback failed",
+///     r"\n            at .+\.rs:\d+:\d+$",
+/// ))
+/// .unwrap();
+/// assert!(re.is_match(msg.as_ref()));
+/// ```
+pub trait Suppress: Error2 + Sized {
+    /// Stores `other`, together with its own backtrace, on this error.
+    #[must_use]
+    fn suppress<S: Error2>(mut self, other: S) -> Self {
+        self.suppress_mut(other);
+        self
+    }
+
+    /// Stores `other`, together with its own backtrace, on this error in place.
+    fn suppress_mut<S: Error2>(&mut self, mut other: S) {
+        // resolves the pending display messages before `other` is dropped
+        other.backtrace();
+
+        let suppressed = mem::replace(other.backtrace_mut(), Backtrace::new());
+        self.backtrace_mut().push_suppressed(suppressed);
+    }
+}
+
+impl<E: Error2> Suppress for E {}
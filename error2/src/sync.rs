@@ -0,0 +1,32 @@
+//! Synchronization primitives that work with and without `std`.
+
+#[cfg(not(feature = "std"))]
+pub(crate) use spin::RwLock;
+
+#[cfg(feature = "std")]
+pub(crate) use self::poison_free::RwLock;
+
+/// A `RwLock` with the locking methods of `spin::RwLock`, poisoning is ignored.
+#[cfg(feature = "std")]
+mod poison_free {
+    use std::sync::{self, PoisonError, RwLockReadGuard, RwLockWriteGuard};
+
+    pub(crate) struct RwLock<T>(sync::RwLock<T>);
+
+    impl<T> RwLock<T> {
+        #[inline]
+        pub(crate) const fn new(value: T) -> Self {
+            Self(sync::RwLock::new(value))
+        }
+
+        #[inline]
+        pub(crate) fn read(&self) -> RwLockReadGuard<'_, T> {
+            self.0.read().unwrap_or_else(PoisonError::into_inner)
+        }
+
+        #[inline]
+        pub(crate) fn write(&self) -> RwLockWriteGuard<'_, T> {
+            self.0.write().unwrap_or_else(PoisonError::into_inner)
+        }
+    }
+}
@@ -0,0 +1,265 @@
+use alloc::string::String;
+use core::{
+    fmt::{self, Write},
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+pub use tracing::Level;
+use tracing_error::{SpanTrace, SpanTraceStatus};
+
+use crate::{Error2, Location};
+
+const NO_LEVEL: u8 = 0;
+
+/// The default level, read by every error pushed, so it is an atomic rather than a lock.
+static DEFAULT_LEVEL: AtomicU8 = AtomicU8::new(NO_LEVEL);
+
+/// Sets the level of the events emitted for errors without their own
+/// `#[error2(level = "...")]` attribute, `None` emits no events. The default is `None`.
+///
+/// An event `error created` is emitted when an error is recorded in its
+/// backtrace, and `error attached` each time it passes through `.attach()`.
+/// Both carry the fields `error.type_name` and `error.location`, the type name
+/// of a [`BoxedError2`](crate::BoxedError2) is the one of the error it wraps.
+///
+/// `error created` also carries `error.message` if the display message was
+/// rendered when the error was created, the messages of `#[error2(lazy)]`
+/// errors are not rendered for events.
+///
+/// # Example
+///
+/// ```
+/// use error2::{prelude::*, trace::Level};
+///
+/// error2::trace::set_default_level(Some(Level::DEBUG));
+///
+/// // emits `error created` at the debug level
+/// let err = ViaRoot("connection reset").build();
+/// assert_eq!(error2::trace::default_level(), Some(Level::DEBUG));
+/// ```
+pub fn set_default_level(level: Option<Level>) {
+    let level = match level {
+        None => NO_LEVEL,
+        Some(Level::ERROR) => 1,
+        Some(Level::WARN) => 2,
+        Some(Level::INFO) => 3,
+        Some(Level::DEBUG) => 4,
+        Some(_) => 5,
+    };
+
+    DEFAULT_LEVEL.store(level, Ordering::Relaxed);
+}
+
+/// Returns the level of the events emitted for errors without their own level.
+#[inline]
+pub fn default_level() -> Option<Level> {
+    match DEFAULT_LEVEL.load(Ordering::Relaxed) {
+        1 => Some(Level::ERROR),
+        2 => Some(Level::WARN),
+        3 => Some(Level::INFO),
+        4 => Some(Level::DEBUG),
+        5 => Some(Level::TRACE),
+        _ => None,
+    }
+}
+
+/// Emits an event at a level only known at runtime, the level of an event
+/// must be a constant for each callsite.
+macro_rules! event_at {
+    ($level:expr, $($args:tt)+) => {
+        match $level {
+            Level::ERROR => tracing::event!(Level::ERROR, $($args)+),
+            Level::WARN => tracing::event!(Level::WARN, $($args)+),
+            Level::INFO => tracing::event!(Level::INFO, $($args)+),
+            Level::DEBUG => tracing::event!(Level::DEBUG, $($args)+),
+            _ => tracing::event!(Level::TRACE, $($args)+),
+        }
+    };
+}
+
+/// Emits the event of an error created at `location`, with its display
+/// message if it was rendered.
+pub(crate) fn created<E: Error2 + ?Sized>(error: &E, message: Option<&str>, location: Location) {
+    emit(error, message, location, "created");
+}
+
+/// Emits the event of an error attached at `location`.
+pub(crate) fn attached<E: Error2 + ?Sized>(error: &E, location: Location) {
+    emit(error, None, location, "attached");
+}
+
+fn emit<E: Error2 + ?Sized>(error: &E, message: Option<&str>, location: Location, action: &str) {
+    let Some(level) = error.event_level().or_else(default_level) else {
+        return;
+    };
+
+    event_at!(
+        level,
+        error.type_name = error.event_type_name(),
+        error.message = message,
+        error.location = %location,
+        "error {}",
+        action,
+    );
+}
+
+/// Captures the active spans, or returns `None` if there are none or the
+/// subscriber does not record them.
+pub(crate) fn span_trace() -> Option<SpanTrace> {
+    let spans = SpanTrace::capture();
+
+    (spans.status() == SpanTraceStatus::CAPTURED).then_some(spans)
+}
+
+/// Renders the spans as `\n    in request{id=7}` lines, the innermost first.
+pub(crate) fn write_spans(msg: &mut String, spans: &SpanTrace) {
+    spans.with_spans(|metadata, fields| {
+        let _ = write_span(msg, metadata.name(), fields);
+        true
+    });
+}
+
+fn write_span(msg: &mut String, name: &str, fields: &str) -> fmt::Result {
+    write!(msg, "\n    in {}", name)?;
+
+    if !fields.is_empty() {
+        write!(msg, "{{{}}}", fields)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        format,
+        string::{String, ToString},
+        sync::{Arc, Mutex},
+        vec::Vec,
+    };
+
+    use tracing::{
+        Event, Subscriber,
+        field::{Field, Visit},
+    };
+    use tracing_error::ErrorLayer;
+    use tracing_subscriber::{Layer, layer::Context, prelude::*};
+
+    use core::fmt;
+
+    use super::Level;
+    use crate::{Attach, Backtrace, BoxedError2, Error2, Location};
+
+    /// Records the level, message and error fields of each event, except the location.
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<(Level, String)>>>);
+
+    impl<S: Subscriber> Layer<S> for Recorder {
+        fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+            struct Fields<'a>(&'a mut String);
+
+            impl Visit for Fields<'_> {
+                fn record_str(&mut self, field: &Field, value: &str) {
+                    self.record_debug(field, &format_args!("{}", value));
+                }
+
+                fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+                    match field.name() {
+                        "message" => self.0.insert_str(0, &format!("{:?}", value)),
+                        "error.location" => {}
+                        name => self.0.push_str(&format!(" {}={:?}", name, value)),
+                    }
+                }
+            }
+
+            let mut fields = String::new();
+            event.record(&mut Fields(&mut fields));
+
+            let level = *event.metadata().level();
+            self.0.lock().unwrap().push((level, fields));
+        }
+    }
+
+    #[derive(Debug)]
+    struct QuotaError {
+        backtrace: Backtrace,
+    }
+
+    impl fmt::Display for QuotaError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("quota exceeded")
+        }
+    }
+
+    impl core::error::Error for QuotaError {}
+
+    impl Error2 for QuotaError {
+        fn backtrace(&self) -> &Backtrace {
+            &self.backtrace
+        }
+
+        fn backtrace_mut(&mut self) -> &mut Backtrace {
+            &mut self.backtrace
+        }
+
+        fn event_level(&self) -> Option<Level> {
+            Some(Level::WARN)
+        }
+    }
+
+    #[test]
+    fn test_events_and_spans() {
+        let recorder = Recorder::default();
+        let subscriber = tracing_subscriber::registry()
+            .with(recorder.clone())
+            .with(ErrorLayer::default());
+
+        let (quota, boxed) = tracing::subscriber::with_default(subscriber, || {
+            let _request = tracing::info_span!("request", id = 7).entered();
+
+            let mut quota = QuotaError {
+                backtrace: Backtrace::new(),
+            };
+            crate::push_error(&mut quota, Location::new("src/quota.rs", 3, 9));
+            let quota = quota.attach_location(Location::new("src/handler.rs", 12, 5));
+
+            // the boxed error is reported with the type and level of the error it wraps
+            let mut retry = QuotaError {
+                backtrace: Backtrace::new(),
+            };
+            crate::push_error(&mut retry, Location::new("src/quota.rs", 5, 9));
+            let _ = BoxedError2::from_err2(retry);
+
+            // without a level of its own, nothing is emitted
+            let boxed = BoxedError2::from_root("timeout").attach();
+
+            (quota, boxed)
+        });
+
+        let created = "error created error.type_name=error2::trace::tests::QuotaError \
+                       error.message=quota exceeded";
+        let attached = "error attached error.type_name=error2::trace::tests::QuotaError";
+
+        let events = recorder.0.lock().unwrap().clone();
+        assert_eq!(
+            events,
+            [
+                (Level::WARN, created.to_string()),
+                (Level::WARN, attached.to_string()),
+                (Level::WARN, created.to_string()),
+                (Level::WARN, created.to_string()),
+            ]
+        );
+
+        // with the `timestamps` and `threads` features, locations are annotated
+        let msg = quota.backtrace().error_message();
+        let re = regex::Regex::new(concat!(
+            r"\n    at src/quota\.rs:3:9[^\n]*",
+            r"\n    in request\{id=7\}",
+            r"\n    at src/handler\.rs:12:5[^\n]*$",
+        ))
+        .unwrap();
+        assert!(re.is_match(&msg), "{}", msg);
+        assert!(boxed.backtrace().span_trace().is_some());
+    }
+}